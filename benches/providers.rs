@@ -0,0 +1,189 @@
+//! Benchmarks of the core providers
+//!
+//! Every provider runs against `fixtures/theme.uss`, a 5k-line theme, and a
+//! synthetic 50k-line style sheet. Compare the results with the committed
+//! baseline using `cargo run --features bench-tools --bin bench-compare` (see
+//! the README).
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use lsp_types::{FormattingOptions, Position, Url};
+use std::collections::HashSet;
+use std::hint::black_box;
+use std::time::Duration;
+use uss_language_server::completion::get_completions;
+use uss_language_server::diagnostics::get_diagnostics;
+use uss_language_server::document::{find_references, format_document, get_colors, Document};
+use uss_language_server::settings::{FormatSettings, Settings};
+
+const THEME: &str = include_str!("fixtures/theme.uss");
+
+/// Number of lines of the synthetic style sheet
+const SYNTHETIC_LINES: usize = 50_000;
+
+/// A benchmarked style sheet
+struct Sheet {
+    name: &'static str,
+    doc: Document,
+}
+
+impl Sheet {
+    fn new(name: &'static str, text: String) -> Self {
+        Self {
+            name,
+            doc: Document::new(text, 0),
+        }
+    }
+
+    /// Position at `column` of the first line from the middle of the sheet on
+    /// that matches `predicate`
+    fn position(
+        &self,
+        predicate: impl Fn(&str) -> bool,
+        column: impl Fn(&str) -> usize,
+    ) -> Position {
+        let text = self.doc.get_text();
+        let lines: Vec<&str> = text.lines().collect();
+        let (line, content) = lines
+            .iter()
+            .enumerate()
+            .skip(lines.len() / 2)
+            .find(|(_, line)| predicate(line))
+            .expect("benchmark position");
+        Position {
+            line: line as u32,
+            character: column(content) as u32,
+        }
+    }
+}
+
+/// Generate a style sheet of `lines` lines of variables and simple rules
+fn synthetic_sheet(lines: usize) -> String {
+    let mut text = String::from(":root {\n");
+    for i in 0..50 {
+        text.push_str(&format!("    --color-{}: #{:06x};\n", i, i * 0x050301));
+    }
+    text.push_str("}\n");
+
+    // Each rule and its blank line take six lines
+    for i in 0..(lines - 52) / 6 {
+        text.push_str(&format!(
+            "\n.item-{} > Label:hover {{\n    color: var(--color-{});\n    margin: {}px 4px;\n    background-color: rgba({}, 64, 128, 0.5);\n}}\n",
+            i,
+            i % 50,
+            i % 32,
+            i % 256,
+        ));
+    }
+    text
+}
+
+fn sheets() -> Vec<Sheet> {
+    vec![
+        Sheet::new("theme-5k", THEME.to_string()),
+        Sheet::new("synthetic-50k", synthetic_sheet(SYNTHETIC_LINES)),
+    ]
+}
+
+fn bench_diagnostics(c: &mut Criterion) {
+    let settings = Settings::default();
+    let variables = HashSet::new();
+    let uri = Url::parse("file:///theme.uss").unwrap();
+    let mut group = c.benchmark_group("diagnostics");
+    for sheet in sheets() {
+        group.bench_function(sheet.name, |b| {
+            b.iter(|| get_diagnostics(black_box(&sheet.doc), &uri, &settings, &variables))
+        });
+    }
+    group.finish();
+}
+
+fn bench_completion(c: &mut Criterion) {
+    let settings = Settings::default();
+    let variables = HashSet::new();
+    let classes = HashSet::new();
+    let mut group = c.benchmark_group("completion");
+    for sheet in sheets() {
+        let declaration = |line: &str| line.starts_with("    ") && line.contains(':');
+        let positions = [
+            (
+                "selector",
+                sheet.position(|line| line.starts_with('.'), |_| 0),
+            ),
+            ("property-name", sheet.position(declaration, |_| 4)),
+            (
+                "property-value",
+                sheet.position(declaration, |line| line.find(':').unwrap() + 2),
+            ),
+        ];
+        for (name, position) in positions {
+            group.bench_function(BenchmarkId::new(name, sheet.name), |b| {
+                b.iter(|| {
+                    get_completions(
+                        black_box(&sheet.doc),
+                        position,
+                        &settings,
+                        &variables,
+                        &classes,
+                    )
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_formatting(c: &mut Criterion) {
+    let options = FormattingOptions {
+        tab_size: 4,
+        insert_spaces: true,
+        ..Default::default()
+    };
+    let mut group = c.benchmark_group("formatting");
+    for sheet in sheets() {
+        group.bench_function(sheet.name, |b| {
+            b.iter(|| format_document(black_box(&sheet.doc), &options, FormatSettings::default()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_colors(c: &mut Criterion) {
+    let mut group = c.benchmark_group("colors");
+    for sheet in sheets() {
+        group.bench_function(sheet.name, |b| b.iter(|| get_colors(black_box(&sheet.doc))));
+    }
+    group.finish();
+}
+
+fn bench_references(c: &mut Criterion) {
+    let settings = Settings::default();
+    let mut group = c.benchmark_group("references");
+    for sheet in sheets() {
+        // A variable used throughout the sheet
+        let position = sheet.position(
+            |line| line.contains("var(--color-"),
+            |line| line.find("--color-").unwrap() + 2,
+        );
+        group.bench_function(sheet.name, |b| {
+            b.iter(|| {
+                find_references(
+                    black_box(&sheet.doc),
+                    position,
+                    "file:///theme.uss",
+                    &settings,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .sample_size(10)
+        .warm_up_time(Duration::from_secs(1))
+        .measurement_time(Duration::from_secs(3));
+    targets = bench_diagnostics, bench_completion, bench_formatting, bench_colors, bench_references
+}
+criterion_main!(benches);
@@ -0,0 +1,477 @@
+//! Analysis API for USS Language Server
+//!
+//! [`analyze`] runs the server's providers over a style sheet and returns
+//! owned, serde-serializable results that do not depend on the LSP wire types.
+//! The server publishes diagnostics and colors through the same functions and
+//! converts the results back, so both report exactly the same findings.
+
+use crate::diagnostics::get_diagnostics;
+use crate::document::{formatted_text, get_colors, Document};
+use crate::parser::parse;
+use crate::settings::Settings;
+use lsp_types::{
+    self, ColorInformation, Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity,
+    DiagnosticTag, FormattingOptions, Location, NumberOrString, Url,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Options of an analysis
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnalysisOptions {
+    /// Settings, as the server receives them in `initializationOptions`
+    pub settings: Settings,
+    /// Variables defined outside the analyzed text, such as in other style sheets
+    pub known_variables: Vec<String>,
+    /// Indentation width of the formatted text
+    pub tab_size: u32,
+    /// Indent the formatted text with spaces instead of tabs
+    pub insert_spaces: bool,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self {
+            settings: Settings::default(),
+            known_variables: Vec::new(),
+            tab_size: 4,
+            insert_spaces: true,
+        }
+    }
+}
+
+/// Everything the analysis found in a style sheet
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisResult {
+    pub diagnostics: Vec<AnalysisDiagnostic>,
+    pub symbols: Symbols,
+    pub colors: Vec<AnalysisColor>,
+    /// The text as the formatter would write it
+    pub formatted: String,
+}
+
+/// A zero-based position; columns count UTF-16 code units, as in LSP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextPosition {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// A range between two positions, end exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextRange {
+    pub start: TextPosition,
+    pub end: TextPosition,
+}
+
+/// Severity of a diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+/// A problem found in the style sheet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisDiagnostic {
+    pub range: TextRange,
+    pub severity: Severity,
+    /// Diagnostic code, also used to enable or disable the check
+    pub code: Option<String>,
+    /// Documentation explaining the code
+    pub code_url: Option<String>,
+    pub message: String,
+    /// Producer of the diagnostic (`uss`)
+    pub source: Option<String>,
+    /// Related locations
+    pub related: Vec<RelatedInformation>,
+    /// The range is unused or unnecessary code
+    pub unnecessary: bool,
+    /// The range uses deprecated syntax
+    pub deprecated: bool,
+    /// Data preserved for code actions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+/// A location related to a diagnostic
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelatedInformation {
+    /// Document of the location; `None` for the analyzed style sheet itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    pub range: TextRange,
+    pub message: String,
+}
+
+/// Symbols defined by the style sheet
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Symbols {
+    pub rules: Vec<RuleSymbol>,
+    pub variables: Vec<VariableSymbol>,
+    pub classes: Vec<ClassSymbol>,
+}
+
+/// A style rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleSymbol {
+    pub selector: String,
+    /// From the selector to the closing brace
+    pub range: TextRange,
+    pub selector_range: TextRange,
+}
+
+/// A variable definition (`--name: value;`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VariableSymbol {
+    pub name: String,
+    pub value: String,
+    /// The range of the name
+    pub range: TextRange,
+}
+
+/// A class name in a selector, without the leading `.`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClassSymbol {
+    pub name: String,
+    /// The range of the name, including the leading `.`
+    pub range: TextRange,
+}
+
+/// A color literal
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AnalysisColor {
+    pub range: TextRange,
+    /// Components in the range 0 to 1
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+/// URI under which [`analyze`] checks its text
+///
+/// Related locations in the text come back without a URI, so it never
+/// appears in the result.
+const ANALYZED_URI: &str = "untitled:stylesheet.uss";
+
+/// Analyze a style sheet
+pub fn analyze(text: &str, options: &AnalysisOptions) -> AnalysisResult {
+    let doc = Document::new(text.to_string(), 0);
+    let known_variables: HashSet<String> = options.known_variables.iter().cloned().collect();
+    let formatting = FormattingOptions {
+        tab_size: options.tab_size,
+        insert_spaces: options.insert_spaces,
+        ..Default::default()
+    };
+
+    let uri = Url::parse(ANALYZED_URI).expect("valid URI");
+
+    AnalysisResult {
+        diagnostics: diagnostics(&doc, &uri, &options.settings, &known_variables),
+        symbols: symbols(&doc),
+        colors: colors(&doc),
+        formatted: formatted_text(&doc, &formatting, options.settings.format),
+    }
+}
+
+/// Diagnostics of the document at `uri`
+pub fn diagnostics(
+    doc: &Document,
+    uri: &Url,
+    settings: &Settings,
+    known_variables: &HashSet<String>,
+) -> Vec<AnalysisDiagnostic> {
+    get_diagnostics(doc, uri, settings, known_variables)
+        .into_iter()
+        .map(|diagnostic| AnalysisDiagnostic::from_lsp(diagnostic, uri))
+        .collect()
+}
+
+/// Color literals of a document
+pub fn colors(doc: &Document) -> Vec<AnalysisColor> {
+    get_colors(doc)
+        .into_iter()
+        .map(AnalysisColor::from)
+        .collect()
+}
+
+/// Rules, variables, and classes defined by a document
+pub fn symbols(doc: &Document) -> Symbols {
+    let text = doc.get_text();
+    let range = |start: usize, end: usize| TextRange {
+        start: doc.byte_offset_to_position(start).into(),
+        end: doc.byte_offset_to_position(end).into(),
+    };
+
+    let stylesheet = parse(&text);
+    let mut symbols = Symbols::default();
+    for rule in &stylesheet.rules {
+        symbols.rules.push(RuleSymbol {
+            selector: rule.selector.clone(),
+            range: range(rule.selector_start, rule.block_end),
+            selector_range: range(rule.selector_start, rule.selector_end),
+        });
+
+        for decl in rule
+            .declarations
+            .iter()
+            .filter(|d| d.property.starts_with("--"))
+        {
+            symbols.variables.push(VariableSymbol {
+                name: decl.property.clone(),
+                value: decl.value.clone(),
+                range: range(decl.property_start, decl.property_end),
+            });
+        }
+    }
+
+    for name in stylesheet.selector_names() {
+        if name.prefix == '.' {
+            symbols.classes.push(ClassSymbol {
+                range: range(name.start - 1, name.end),
+                name: name.name,
+            });
+        }
+    }
+
+    symbols
+}
+
+impl From<lsp_types::Position> for TextPosition {
+    fn from(position: lsp_types::Position) -> Self {
+        Self {
+            line: position.line,
+            character: position.character,
+        }
+    }
+}
+
+impl From<TextPosition> for lsp_types::Position {
+    fn from(position: TextPosition) -> Self {
+        Self {
+            line: position.line,
+            character: position.character,
+        }
+    }
+}
+
+impl From<lsp_types::Range> for TextRange {
+    fn from(range: lsp_types::Range) -> Self {
+        Self {
+            start: range.start.into(),
+            end: range.end.into(),
+        }
+    }
+}
+
+impl From<TextRange> for lsp_types::Range {
+    fn from(range: TextRange) -> Self {
+        Self {
+            start: range.start.into(),
+            end: range.end.into(),
+        }
+    }
+}
+
+impl From<Severity> for DiagnosticSeverity {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Information => DiagnosticSeverity::INFORMATION,
+            Severity::Hint => DiagnosticSeverity::HINT,
+        }
+    }
+}
+
+impl AnalysisDiagnostic {
+    /// Convert an LSP diagnostic of the document at `uri`
+    ///
+    /// Related locations in that document lose their URI; [`Self::into_lsp`]
+    /// restores it, so the conversion round-trips.
+    pub fn from_lsp(diagnostic: Diagnostic, uri: &Url) -> Self {
+        let severity = match diagnostic.severity {
+            Some(DiagnosticSeverity::WARNING) => Severity::Warning,
+            Some(DiagnosticSeverity::INFORMATION) => Severity::Information,
+            Some(DiagnosticSeverity::HINT) => Severity::Hint,
+            _ => Severity::Error,
+        };
+        let tags = diagnostic.tags.unwrap_or_default();
+
+        Self {
+            range: diagnostic.range.into(),
+            severity,
+            code: diagnostic.code.map(|code| match code {
+                NumberOrString::String(code) => code,
+                NumberOrString::Number(code) => code.to_string(),
+            }),
+            code_url: diagnostic
+                .code_description
+                .map(|description| description.href.to_string()),
+            message: diagnostic.message,
+            source: diagnostic.source,
+            related: diagnostic
+                .related_information
+                .unwrap_or_default()
+                .into_iter()
+                .map(|info| RelatedInformation {
+                    uri: (info.location.uri != *uri).then(|| info.location.uri.to_string()),
+                    range: info.location.range.into(),
+                    message: info.message,
+                })
+                .collect(),
+            unnecessary: tags.contains(&DiagnosticTag::UNNECESSARY),
+            deprecated: tags.contains(&DiagnosticTag::DEPRECATED),
+            data: diagnostic.data,
+        }
+    }
+
+    /// Convert to an LSP diagnostic of the document at `uri`
+    ///
+    /// Numeric codes come back as strings; everything else is preserved.
+    pub fn into_lsp(self, uri: &Url) -> Diagnostic {
+        let mut tags = Vec::new();
+        if self.unnecessary {
+            tags.push(DiagnosticTag::UNNECESSARY);
+        }
+        if self.deprecated {
+            tags.push(DiagnosticTag::DEPRECATED);
+        }
+        let related: Vec<DiagnosticRelatedInformation> = self
+            .related
+            .into_iter()
+            .map(|info| DiagnosticRelatedInformation {
+                location: Location {
+                    uri: info
+                        .uri
+                        .and_then(|related| Url::parse(&related).ok())
+                        .unwrap_or_else(|| uri.clone()),
+                    range: info.range.into(),
+                },
+                message: info.message,
+            })
+            .collect();
+
+        Diagnostic {
+            range: self.range.into(),
+            severity: Some(self.severity.into()),
+            code: self.code.map(NumberOrString::String),
+            code_description: self
+                .code_url
+                .and_then(|url| Url::parse(&url).ok())
+                .map(|href| lsp_types::CodeDescription { href }),
+            source: self.source,
+            message: self.message,
+            related_information: (!related.is_empty()).then_some(related),
+            tags: (!tags.is_empty()).then_some(tags),
+            data: self.data,
+        }
+    }
+}
+
+impl From<ColorInformation> for AnalysisColor {
+    fn from(info: ColorInformation) -> Self {
+        Self {
+            range: info.range.into(),
+            red: info.color.red,
+            green: info.color.green,
+            blue: info.color.blue,
+            alpha: info.color.alpha,
+        }
+    }
+}
+
+impl From<AnalysisColor> for ColorInformation {
+    fn from(color: AnalysisColor) -> Self {
+        Self {
+            range: color.range.into(),
+            color: lsp_types::Color {
+                red: color.red,
+                green: color.green,
+                blue: color.blue,
+                alpha: color.alpha,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{CodeDescription, Position, Range};
+    use serde_json::json;
+
+    fn range(line: u32) -> Range {
+        Range::new(Position::new(line, 1), Position::new(line, 4))
+    }
+
+    fn uri() -> Url {
+        Url::parse("file:///theme.uss").unwrap()
+    }
+
+    #[test]
+    fn diagnostics_round_trip_through_serde() {
+        let other = Url::parse("file:///tokens.uss").unwrap();
+        let diagnostic = Diagnostic {
+            range: range(3),
+            severity: Some(DiagnosticSeverity::HINT),
+            code: Some(NumberOrString::String("shadowed-declaration".into())),
+            code_description: Some(CodeDescription {
+                href: Url::parse("https://example.com/rules").unwrap(),
+            }),
+            source: Some("uss".into()),
+            message: "Overridden".into(),
+            related_information: Some(vec![
+                DiagnosticRelatedInformation {
+                    location: Location::new(uri(), range(5)),
+                    message: "here".into(),
+                },
+                DiagnosticRelatedInformation {
+                    location: Location::new(other, range(0)),
+                    message: "defined there".into(),
+                },
+            ]),
+            tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+            data: Some(json!({ "fix": "remove" })),
+        };
+
+        let analysis = AnalysisDiagnostic::from_lsp(diagnostic.clone(), &uri());
+        assert_eq!(analysis.related[0].uri, None);
+        assert_eq!(
+            analysis.related[1].uri.as_deref(),
+            Some("file:///tokens.uss")
+        );
+
+        let json = serde_json::to_string(&analysis).unwrap();
+        let parsed: AnalysisDiagnostic = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, analysis);
+        assert_eq!(parsed.into_lsp(&uri()), diagnostic);
+    }
+
+    #[test]
+    fn related_locations_use_the_document_uri() {
+        let doc = Document::new(".a { color: red; }\n.a { color: blue; }\n".to_string(), 1);
+        let diagnostics = get_diagnostics(&doc, &uri(), &Settings::default(), &HashSet::new());
+        let shadowed = diagnostics
+            .iter()
+            .find(|d| d.code == Some(NumberOrString::String("shadowed-declaration".into())))
+            .unwrap();
+        let related = shadowed.related_information.as_ref().unwrap();
+        assert_eq!(related[0].location.uri, uri());
+
+        // The URI-less analysis API never exposes the URI it checks under
+        let result = analyze(&doc.get_text(), &AnalysisOptions::default());
+        assert!(result
+            .diagnostics
+            .iter()
+            .all(|d| d.related.iter().all(|r| r.uri.is_none())));
+    }
+}
@@ -0,0 +1,203 @@
+//! Compare benchmark results with the committed baseline
+//!
+//! Reads the mean time of every benchmark from criterion's output and fails
+//! when one is slower than the baseline by more than the threshold:
+//!
+//! ```text
+//! cargo bench
+//! cargo run --features bench-tools --bin bench-compare -- [--threshold PERCENT] [--baseline FILE] [--criterion-dir DIR]
+//! cargo run --features bench-tools --bin bench-compare -- --save
+//! ```
+//!
+//! `--save` replaces the baseline with the current results instead.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str =
+    "usage: bench-compare [--save] [--threshold PERCENT] [--baseline FILE] [--criterion-dir DIR]";
+
+/// Default allowed slowdown, in percent
+const DEFAULT_THRESHOLD: f64 = 25.0;
+
+fn main() -> ExitCode {
+    let mut save = false;
+    let mut threshold = DEFAULT_THRESHOLD;
+    let mut baseline_path = PathBuf::from("benches/baseline.json");
+    let mut criterion_dir = PathBuf::from("target/criterion");
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--save" => save = true,
+            "--threshold" => match rest.next().and_then(|t| t.parse().ok()) {
+                Some(t) => threshold = t,
+                None => {
+                    eprintln!("--threshold expects a percentage\n{}", USAGE);
+                    return ExitCode::from(2);
+                }
+            },
+            "--baseline" => match rest.next() {
+                Some(path) => baseline_path = PathBuf::from(path),
+                None => {
+                    eprintln!("--baseline expects a file path\n{}", USAGE);
+                    return ExitCode::from(2);
+                }
+            },
+            "--criterion-dir" => match rest.next() {
+                Some(path) => criterion_dir = PathBuf::from(path),
+                None => {
+                    eprintln!("--criterion-dir expects a directory\n{}", USAGE);
+                    return ExitCode::from(2);
+                }
+            },
+            _ => {
+                eprintln!("unexpected argument '{}'\n{}", arg, USAGE);
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    let mut current = BTreeMap::new();
+    if let Err(e) = collect_results(&criterion_dir, &criterion_dir, &mut current) {
+        eprintln!("cannot read {}: {}", criterion_dir.display(), e);
+        return ExitCode::FAILURE;
+    }
+    if current.is_empty() {
+        eprintln!(
+            "no results in {}; run `cargo bench` first",
+            criterion_dir.display()
+        );
+        return ExitCode::FAILURE;
+    }
+
+    if save {
+        let rounded: BTreeMap<&String, f64> = current.iter().map(|(k, v)| (k, v.round())).collect();
+        let json = serde_json::to_string_pretty(&rounded).expect("serializable results");
+        if let Err(e) = std::fs::write(&baseline_path, json + "\n") {
+            eprintln!("cannot write {}: {}", baseline_path.display(), e);
+            return ExitCode::FAILURE;
+        }
+        println!(
+            "saved {} results to {}",
+            current.len(),
+            baseline_path.display()
+        );
+        return ExitCode::SUCCESS;
+    }
+
+    let baseline: BTreeMap<String, f64> = match std::fs::read_to_string(&baseline_path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+    {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("cannot read {}: {}", baseline_path.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut regressions = 0;
+    println!(
+        "{:<40} {:>12} {:>12} {:>8}",
+        "benchmark", "baseline", "current", "change"
+    );
+    for (name, &time) in &current {
+        let Some(&base) = baseline.get(name) else {
+            println!(
+                "{:<40} {:>12} {:>12} {:>8}",
+                name,
+                "-",
+                format_time(time),
+                "new"
+            );
+            continue;
+        };
+        let change = (time - base) / base * 100.0;
+        let regressed = change > threshold;
+        regressions += usize::from(regressed);
+        println!(
+            "{:<40} {:>12} {:>12} {:>+7.1}%{}",
+            name,
+            format_time(base),
+            format_time(time),
+            change,
+            if regressed { "  REGRESSED" } else { "" }
+        );
+    }
+    for (name, &base) in baseline
+        .iter()
+        .filter(|(name, _)| !current.contains_key(*name))
+    {
+        println!(
+            "{:<40} {:>12} {:>12} {:>8}",
+            name,
+            format_time(base),
+            "-",
+            "missing"
+        );
+    }
+
+    if regressions > 0 {
+        eprintln!(
+            "{} benchmark(s) regressed by more than {}%",
+            regressions, threshold
+        );
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// Collect the mean time in nanoseconds of every benchmark under `dir`
+///
+/// Criterion writes `<group>/<benchmark>/new/estimates.json`; the path below
+/// the criterion directory names the benchmark.
+fn collect_results(
+    root: &Path,
+    dir: &Path,
+    results: &mut BTreeMap<String, f64>,
+) -> std::io::Result<()> {
+    let estimates = dir.join("new").join("estimates.json");
+    if estimates.is_file() {
+        let json: Value = serde_json::from_str(&std::fs::read_to_string(&estimates)?)?;
+        if let Some(mean) = json["mean"]["point_estimate"].as_f64() {
+            let name = dir
+                .strip_prefix(root)
+                .unwrap_or(dir)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            results.insert(name, mean);
+        }
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        // `report` holds criterion's HTML summary, not results
+        if path.is_dir()
+            && path
+                .file_name()
+                .is_some_and(|n| n != "new" && n != "base" && n != "report")
+        {
+            collect_results(root, &path, results)?;
+        }
+    }
+    Ok(())
+}
+
+/// Format nanoseconds with a readable unit
+fn format_time(ns: f64) -> String {
+    if ns >= 1e9 {
+        format!("{:.2} s", ns / 1e9)
+    } else if ns >= 1e6 {
+        format!("{:.2} ms", ns / 1e6)
+    } else if ns >= 1e3 {
+        format!("{:.2} µs", ns / 1e3)
+    } else {
+        format!("{:.0} ns", ns)
+    }
+}
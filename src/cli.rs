@@ -0,0 +1,132 @@
+//! Command line interface for USS Language Server
+//!
+//! Runs one-off commands over a directory instead of starting the server:
+//!
+//! ```text
+//! uss-language-server report variables [--format markdown|csv] [--output FILE] [ROOT]
+//! uss-language-server analyze FILE
+//! ```
+
+use crate::analysis::{analyze, AnalysisOptions};
+use crate::report::{variables_report, ReportFormat};
+use crate::workspace::WorkspaceIndex;
+use std::path::PathBuf;
+
+const USAGE: &str =
+    "usage: uss-language-server report variables [--format markdown|csv] [--output FILE] [ROOT]";
+
+const ANALYZE_USAGE: &str = "usage: uss-language-server analyze FILE";
+
+/// Run a command line invocation
+///
+/// Returns `None` when the arguments are not a command, in which case the
+/// language server should start (editors may pass flags such as `--stdio`).
+/// Otherwise returns the process exit code.
+pub fn run(args: &[String]) -> Option<i32> {
+    match args.first().map(String::as_str) {
+        Some("report") => Some(report(&args[1..])),
+        Some("analyze") => Some(analyze_file(&args[1..])),
+        _ => None,
+    }
+}
+
+fn report(args: &[String]) -> i32 {
+    if args.first().map(String::as_str) != Some("variables") {
+        eprintln!("{}", USAGE);
+        return 2;
+    }
+
+    let mut format = ReportFormat::Markdown;
+    let mut output = None;
+    let mut root = None;
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--format" => match rest.next().and_then(|f| ReportFormat::from_name(f)) {
+                Some(f) => format = f,
+                None => {
+                    eprintln!("--format expects markdown or csv\n{}", USAGE);
+                    return 2;
+                }
+            },
+            "--output" => match rest.next() {
+                Some(path) => output = Some(PathBuf::from(path)),
+                None => {
+                    eprintln!("--output expects a file path\n{}", USAGE);
+                    return 2;
+                }
+            },
+            _ if root.is_none() && !arg.starts_with('-') => root = Some(PathBuf::from(arg)),
+            _ => {
+                eprintln!("unexpected argument '{}'\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+    }
+
+    let root = match root.map_or_else(std::env::current_dir, std::fs::canonicalize) {
+        Ok(root) => root,
+        Err(e) => {
+            eprintln!("cannot read workspace root: {}", e);
+            return 1;
+        }
+    };
+
+    let index = WorkspaceIndex::new();
+    match index.scan(&root) {
+        Ok(summary) if summary.skipped > 0 => {
+            eprintln!(
+                "skipped {} unreadable file(s) or directories",
+                summary.skipped
+            );
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("cannot read {}: {}", root.display(), e);
+            return 1;
+        }
+    }
+    let report = variables_report(&index.snapshot(), Some(&root), format);
+
+    match output {
+        Some(path) => match std::fs::write(&path, report) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("cannot write {}: {}", path.display(), e);
+                1
+            }
+        },
+        None => {
+            print!("{}", report);
+            0
+        }
+    }
+}
+
+/// Print the analysis of a style sheet as JSON, as the WASM `analyze_json` export does
+fn analyze_file(args: &[String]) -> i32 {
+    let [path] = args else {
+        eprintln!("{}", ANALYZE_USAGE);
+        return 2;
+    };
+
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("cannot read {}: {}", path, e);
+            return 1;
+        }
+    };
+
+    let result = analyze(&text, &AnalysisOptions::default());
+    match serde_json::to_string(&result) {
+        Ok(json) => {
+            println!("{}", json);
+            0
+        }
+        Err(e) => {
+            eprintln!("cannot serialize the analysis: {}", e);
+            1
+        }
+    }
+}
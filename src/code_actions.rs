@@ -0,0 +1,830 @@
+//! Code actions for USS Language Server
+//!
+//! Provides quick fixes for diagnostics reported by the server and
+//! refactorings at the cursor.
+
+use crate::diagnostics::COMPANION_RULES;
+use crate::document::{defined_variables, Document};
+use crate::parser::{normalize_selector, parse, Rule, Stylesheet};
+use crate::values::{literal_key, value_literals};
+use lsp_types::*;
+use std::collections::{HashMap, HashSet};
+
+/// Get code actions for the diagnostics in a range
+pub fn get_code_actions(
+    doc: &Document,
+    uri: &Url,
+    diagnostics: &[Diagnostic],
+) -> Vec<CodeActionOrCommand> {
+    let mut actions = Vec::new();
+
+    for diagnostic in diagnostics {
+        let code = match &diagnostic.code {
+            Some(NumberOrString::String(code)) => code.as_str(),
+            _ => continue,
+        };
+
+        if let Some(rule) = COMPANION_RULES.iter().find(|r| r.code == code) {
+            let edit = insert_declaration_before(doc, diagnostic.range.start, rule.stub);
+            actions.push(quick_fix(
+                format!("Add {} declaration", rule.companion),
+                uri,
+                vec![edit],
+                diagnostic,
+            ));
+        } else if code == "missing-semicolon" {
+            let edit = TextEdit {
+                range: Range {
+                    start: diagnostic.range.end,
+                    end: diagnostic.range.end,
+                },
+                new_text: ";".to_string(),
+            };
+            actions.push(quick_fix(
+                "Add semicolon".to_string(),
+                uri,
+                vec![edit],
+                diagnostic,
+            ));
+        } else if code == "wrong-separator" {
+            if let Some(edit) = swap_separator(doc, diagnostic.range) {
+                let title = if edit.new_text == " " {
+                    "Replace comma with space"
+                } else {
+                    "Replace space with comma"
+                };
+                actions.push(quick_fix(title.to_string(), uri, vec![edit], diagnostic));
+            }
+        } else if let Some(title) = match code {
+            "pseudo-class-space" => Some("Remove space after ':'"),
+            "pseudo-class-double-colon" => Some("Use a single colon"),
+            "pseudo-class-semicolon" => Some("Replace ';' with ':'"),
+            _ => None,
+        } {
+            let edit = TextEdit {
+                range: diagnostic.range,
+                new_text: ":".to_string(),
+            };
+            actions.push(quick_fix(title.to_string(), uri, vec![edit], diagnostic));
+        } else if code == "shadowed-declaration" {
+            let edit = delete_range(doc, diagnostic.range);
+            actions.push(quick_fix(
+                "Remove overridden declaration".to_string(),
+                uri,
+                vec![edit],
+                diagnostic,
+            ));
+        } else if code == "variable-used-before-definition" {
+            actions.extend(move_definition_before_usage(doc, uri, diagnostic));
+        } else if code == "identical-rule-body" {
+            actions.extend(merge_identical_rules(doc, uri, diagnostic));
+        }
+    }
+
+    actions
+}
+
+/// Minimum number of occurrences for the "replace with a new variable" refactoring
+const MIN_REPEATED_LITERALS: usize = 3;
+
+/// Get refactorings available at a position
+pub fn get_refactor_actions(
+    doc: &Document,
+    uri: &Url,
+    position: Position,
+) -> Vec<CodeActionOrCommand> {
+    let mut actions = Vec::new();
+    actions.extend(replace_repeated_literal(doc, uri, position));
+    actions
+}
+
+/// Offer replacing every occurrence of the literal under the cursor with a new variable
+///
+/// Only literals in regular declarations count; variable definitions, var()
+/// fallbacks, and comments are left alone.
+fn replace_repeated_literal(
+    doc: &Document,
+    uri: &Url,
+    position: Position,
+) -> Option<CodeActionOrCommand> {
+    let text = doc.get_text();
+    let offset = doc.position_to_byte_offset(position)?;
+    let stylesheet = parse(&text);
+
+    // (absolute start, absolute end, key) of every literal
+    let literals: Vec<(usize, usize, String)> = stylesheet
+        .rules
+        .iter()
+        .flat_map(|rule| &rule.declarations)
+        .filter(|decl| !decl.property.starts_with("--"))
+        .flat_map(|decl| {
+            value_literals(&decl.value).into_iter().map(|literal| {
+                (
+                    decl.value_start + literal.start,
+                    decl.value_start + literal.end,
+                    literal_key(literal.text),
+                )
+            })
+        })
+        .collect();
+
+    let (start, end, key) = literals
+        .iter()
+        .find(|(start, end, _)| *start <= offset && offset <= *end)?;
+    let literal = &text[*start..*end];
+    let occurrences: Vec<_> = literals.iter().filter(|(_, _, k)| k == key).collect();
+    if occurrences.len() < MIN_REPEATED_LITERALS {
+        return None;
+    }
+
+    let name = suggest_variable_name(literal, &defined_variables(&text));
+    let mut edits = vec![variable_definition_edit(
+        doc,
+        &text,
+        &stylesheet,
+        &name,
+        literal,
+    )?];
+    for (start, end, _) in &occurrences {
+        edits.push(TextEdit {
+            range: Range {
+                start: doc.byte_offset_to_position(*start),
+                end: doc.byte_offset_to_position(*end),
+            },
+            new_text: format!("var({})", name),
+        });
+    }
+
+    let mut changes = HashMap::new();
+    changes.insert(uri.clone(), edits);
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!(
+            "Replace all {} occurrences of {} with a new variable",
+            occurrences.len(),
+            literal
+        ),
+        kind: Some(CodeActionKind::REFACTOR_EXTRACT),
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        ..Default::default()
+    }))
+}
+
+/// Build an edit defining a variable in `:root`, creating the rule if needed
+fn variable_definition_edit(
+    doc: &Document,
+    text: &str,
+    stylesheet: &Stylesheet,
+    name: &str,
+    value: &str,
+) -> Option<TextEdit> {
+    // Indent like the first declaration of the file on its own line
+    let indent = stylesheet
+        .rules
+        .iter()
+        .flat_map(|rule| &rule.declarations)
+        .map(|decl| {
+            let line_start = text[..decl.property_start].rfind('\n').map_or(0, |i| i + 1);
+            &text[line_start..decl.property_start]
+        })
+        .find(|indent| !indent.is_empty() && indent.trim().is_empty())
+        .unwrap_or("    ");
+
+    let (offset, new_text) = match stylesheet.rules.iter().find(|r| r.selector == ":root") {
+        Some(root) => (
+            root.block_start + 1,
+            format!("\n{}{}: {};", indent, name, value),
+        ),
+        None => (
+            stylesheet.rules.first()?.selector_start,
+            format!(":root {{\n{}{}: {};\n}}\n\n", indent, name, value),
+        ),
+    };
+    let position = doc.byte_offset_to_position(offset);
+
+    Some(TextEdit {
+        range: Range {
+            start: position,
+            end: position,
+        },
+        new_text,
+    })
+}
+
+/// Suggest a name for a variable holding a literal, avoiding existing names
+fn suggest_variable_name(literal: &str, existing: &HashSet<String>) -> String {
+    let lower = literal.to_ascii_lowercase();
+    let base = if let Some(hex) = lower.strip_prefix('#') {
+        format!("--color-{}", hex)
+    } else if lower.starts_with("rgb") || lower.starts_with("hsl") {
+        "--color".to_string()
+    } else if lower.starts_with("url") || lower.starts_with("resource") {
+        "--image".to_string()
+    } else if lower.starts_with(|c: char| c.is_ascii_digit() || c == '.' || c == '-') {
+        let slug: String = lower
+            .chars()
+            .map(|c| match c {
+                '.' => '-',
+                '%' => 'p',
+                c if c.is_ascii_alphanumeric() || c == '-' => c,
+                _ => '-',
+            })
+            .collect();
+        format!("--size-{}", slug.trim_start_matches('-'))
+    } else {
+        "--value".to_string()
+    };
+
+    if !existing.contains(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|name| !existing.contains(name))
+        .expect("an unused variable name exists")
+}
+
+/// Get source actions available for a whole document
+pub fn get_source_actions(uri: &Url) -> Vec<CodeActionOrCommand> {
+    vec![CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Preview formatted file".to_string(),
+        kind: Some(CodeActionKind::SOURCE),
+        command: Some(Command {
+            title: "Preview formatted file".to_string(),
+            command: "uss.previewFormatted".to_string(),
+            arguments: Some(vec![serde_json::Value::String(uri.to_string())]),
+        }),
+        ..Default::default()
+    })]
+}
+
+/// Build an edit inserting a declaration before the declaration at a position
+///
+/// When the declaration starts its line, the new one goes on its own line with
+/// the same indentation; otherwise it is inserted inline.
+fn insert_declaration_before(doc: &Document, position: Position, declaration: &str) -> TextEdit {
+    let prefix = doc.get_text_before_cursor(position).unwrap_or_default();
+
+    if prefix.trim().is_empty() {
+        TextEdit {
+            range: Range {
+                start: Position {
+                    line: position.line,
+                    character: 0,
+                },
+                end: Position {
+                    line: position.line,
+                    character: 0,
+                },
+            },
+            new_text: format!("{}{}\n", prefix, declaration),
+        }
+    } else {
+        TextEdit {
+            range: Range {
+                start: position,
+                end: position,
+            },
+            new_text: format!("{} ", declaration),
+        }
+    }
+}
+
+/// Build an edit swapping the separator in a range
+///
+/// A comma becomes a single space, absorbing whitespace around it; whitespace
+/// becomes `, `.
+fn swap_separator(doc: &Document, range: Range) -> Option<TextEdit> {
+    if range.start.line != range.end.line {
+        return None;
+    }
+    let text = doc.get_text();
+    let start = doc.position_to_byte_offset(range.start)?;
+    let end = doc.position_to_byte_offset(range.end)?;
+    let separator = &text[start..end];
+
+    let is_blank = |c: char| c == ' ' || c == '\t';
+    let (start, end, new_text) = if separator == "," {
+        let before = text[..start].len() - text[..start].trim_end_matches(is_blank).len();
+        let after = text[end..].len() - text[end..].trim_start_matches(is_blank).len();
+        (start - before, end + after, " ")
+    } else if !separator.is_empty() && separator.trim().is_empty() {
+        (start, end, ", ")
+    } else {
+        return None;
+    };
+
+    Some(TextEdit {
+        range: Range {
+            start: doc.byte_offset_to_position(start),
+            end: doc.byte_offset_to_position(end),
+        },
+        new_text: new_text.to_string(),
+    })
+}
+
+/// Delete a range, along with its line if nothing else is on it
+///
+/// Mid-line, one space after the range goes too if there is whitespace before
+/// it, so `{ a; b; }` becomes `{ b; }`.
+fn delete_range(doc: &Document, range: Range) -> TextEdit {
+    let line = doc.get_line(range.start.line).unwrap_or_default();
+    let start = doc.codec.byte_offset(&line, range.start.character);
+    let end = if range.end.line == range.start.line {
+        doc.codec.byte_offset(&line, range.end.character)
+    } else {
+        line.len()
+    };
+
+    let whole_line = line[..start].trim().is_empty() && line[end..].trim().is_empty();
+    let range = if whole_line && range.end.line == range.start.line {
+        Range {
+            start: Position {
+                line: range.start.line,
+                character: 0,
+            },
+            end: Position {
+                line: range.start.line + 1,
+                character: 0,
+            },
+        }
+    } else if range.end.line == range.start.line
+        && line[..start].ends_with(char::is_whitespace)
+        && line[end..].starts_with(' ')
+    {
+        Range {
+            end: Position {
+                character: range.end.character + 1,
+                ..range.end
+            },
+            ..range
+        }
+    } else {
+        range
+    };
+
+    TextEdit {
+        range,
+        new_text: String::new(),
+    }
+}
+
+/// Move the definition of a variable above the declaration using it
+fn move_definition_before_usage(
+    doc: &Document,
+    uri: &Url,
+    diagnostic: &Diagnostic,
+) -> Option<CodeActionOrCommand> {
+    let text = doc.get_text();
+    let offset = doc.position_to_byte_offset(diagnostic.range.start)?;
+    let end = doc.position_to_byte_offset(diagnostic.range.end)?;
+    let name = &text[offset..end];
+    let stylesheet = parse(&text);
+    let rule = stylesheet.rule_at(offset)?;
+    let usage = rule
+        .declarations
+        .iter()
+        .find(|d| d.value_start <= offset && offset < d.value_end)?;
+    let definition = rule
+        .declarations
+        .iter()
+        .find(|d| d.property == name && d.property_start > usage.end)?;
+
+    let mut declaration = text[definition.property_start..definition.end].to_string();
+    if !definition.has_semicolon {
+        declaration.push(';');
+    }
+    let insert = insert_declaration_before(
+        doc,
+        doc.byte_offset_to_position(usage.property_start),
+        &declaration,
+    );
+
+    // Take the whitespace before the definition along unless it is on its own line
+    let line_start = text[..definition.property_start]
+        .rfind('\n')
+        .map_or(0, |i| i + 1);
+    let start = if text[line_start..definition.property_start]
+        .trim()
+        .is_empty()
+    {
+        definition.property_start
+    } else {
+        text[..definition.property_start].trim_end().len()
+    };
+    let delete = delete_range(
+        doc,
+        Range {
+            start: doc.byte_offset_to_position(start),
+            end: doc.byte_offset_to_position(definition.end),
+        },
+    );
+
+    Some(quick_fix(
+        format!("Move '{}' definition above its usage", name),
+        uri,
+        vec![insert, delete],
+        diagnostic,
+    ))
+}
+
+/// Merge the rules with the same declarations as the diagnostic's rule into the first one
+///
+/// Merging moves later rules up to the first one, so a rule is left alone when
+/// a rule in between has one of its selectors; it would override it otherwise.
+fn merge_identical_rules(
+    doc: &Document,
+    uri: &Url,
+    diagnostic: &Diagnostic,
+) -> Option<CodeActionOrCommand> {
+    let text = doc.get_text();
+    let offset = doc.position_to_byte_offset(diagnostic.range.start)?;
+    let rules = parse(&text).rules;
+    let index = rules.iter().position(|r| r.selector_start == offset)?;
+    let body = rules[index].normalized_body();
+    let group: Vec<usize> = (0..rules.len())
+        .filter(|&i| !rules[i].declarations.is_empty() && rules[i].normalized_body() == body)
+        .collect();
+    let first = *group.first()?;
+
+    let selectors: Vec<Vec<String>> = rules
+        .iter()
+        .map(|rule| {
+            rule.selectors()
+                .into_iter()
+                .map(normalize_selector)
+                .collect()
+        })
+        .collect();
+    let mergeable: Vec<usize> = group[1..]
+        .iter()
+        .copied()
+        .filter(|&later| {
+            !selectors[first + 1..later]
+                .iter()
+                .any(|between| selectors[later].iter().any(|s| between.contains(s)))
+        })
+        .collect();
+    if !mergeable.contains(&index) {
+        return None;
+    }
+
+    let merged: Vec<&str> = std::iter::once(first)
+        .chain(mergeable.iter().copied())
+        .map(|i| rules[i].selector.as_str())
+        .collect();
+    let mut edits = vec![TextEdit {
+        range: Range {
+            start: doc.byte_offset_to_position(rules[first].selector_start),
+            end: doc.byte_offset_to_position(rules[first].selector_end),
+        },
+        new_text: merged.join(", "),
+    }];
+    edits.extend(
+        mergeable
+            .iter()
+            .map(|&i| delete_rule(doc, &text, &rules[i])),
+    );
+
+    Some(quick_fix(
+        "Merge with identical rules".to_string(),
+        uri,
+        edits,
+        diagnostic,
+    ))
+}
+
+/// Delete a rule, with its lines and one adjacent blank line if it is on lines of its own
+fn delete_rule(doc: &Document, text: &str, rule: &Rule) -> TextEdit {
+    let line_start = text[..rule.selector_start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = text[rule.block_end..]
+        .find('\n')
+        .map_or(text.len(), |i| rule.block_end + i + 1);
+
+    let (mut start, mut end) = (rule.selector_start, rule.block_end);
+    if text[line_start..start].trim().is_empty() && text[end..line_end].trim().is_empty() {
+        start = line_start;
+        end = line_end;
+
+        // Keep a single blank line between the surrounding rules
+        let after_blank = text[end..]
+            .find('\n')
+            .filter(|&i| text[end..end + i].trim().is_empty())
+            .map(|i| end + i + 1);
+        let before_blank = start == 0 || text[..start - 1].ends_with('\n');
+        match after_blank {
+            Some(after_blank) if before_blank => end = after_blank,
+            None if before_blank && start > 0 && end == text.len() => start -= 1,
+            _ => {}
+        }
+    }
+
+    TextEdit {
+        range: Range {
+            start: doc.byte_offset_to_position(start),
+            end: doc.byte_offset_to_position(end),
+        },
+        new_text: String::new(),
+    }
+}
+
+/// Build a quick fix code action for a diagnostic
+fn quick_fix(
+    title: String,
+    uri: &Url,
+    edits: Vec<TextEdit>,
+    diagnostic: &Diagnostic,
+) -> CodeActionOrCommand {
+    let mut changes = HashMap::new();
+    changes.insert(uri.clone(), edits);
+
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }),
+        is_preferred: Some(true),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::get_diagnostics;
+    use crate::settings::Settings;
+
+    const URI: &str = "file:///theme.uss";
+
+    /// Titles and results of the quick fixes for the diagnostics with a code
+    ///
+    /// The code's check is enabled even if it is opt-in.
+    fn fixes(text: &str, code: &str) -> Vec<(String, String)> {
+        let uri: Url = URI.parse().unwrap();
+        let doc = Document::new(text.to_string(), 1);
+        let mut settings = Settings::default();
+        settings.diagnostics.rules.insert(code.to_string(), true);
+        let diagnostics: Vec<Diagnostic> = get_diagnostics(&doc, &uri, &settings, &HashSet::new())
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String(code.to_string())))
+            .collect();
+
+        get_code_actions(&doc, &uri, &diagnostics)
+            .into_iter()
+            .map(|action| applied(&doc, action))
+            .collect()
+    }
+
+    /// Title of an action and the document after applying its edits
+    fn applied(doc: &Document, action: CodeActionOrCommand) -> (String, String) {
+        let uri: Url = URI.parse().unwrap();
+        let CodeActionOrCommand::CodeAction(action) = action else {
+            panic!("expected a code action");
+        };
+        let mut edits = action.edit.unwrap().changes.unwrap().remove(&uri).unwrap();
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+        let mut fixed = doc.clone();
+        for edit in edits {
+            fixed.apply_change(edit.range, &edit.new_text);
+        }
+        (action.title, fixed.get_text())
+    }
+
+    /// Titles and results of the refactorings at the `|` in a text
+    fn refactorings(text: &str) -> Vec<(String, String)> {
+        let offset = text.find('|').unwrap();
+        let doc = Document::new(text.replacen('|', "", 1), 1);
+        let position = doc.byte_offset_to_position(offset);
+        get_refactor_actions(&doc, &URI.parse().unwrap(), position)
+            .into_iter()
+            .map(|action| applied(&doc, action))
+            .collect()
+    }
+
+    fn fix(text: &str, code: &str) -> (String, String) {
+        let mut fixes = fixes(text, code);
+        assert_eq!(fixes.len(), 1, "{:?}", fixes);
+        fixes.remove(0)
+    }
+
+    #[test]
+    fn comma_replaced_with_space() {
+        assert_eq!(
+            fix(".a { margin: 10px , 5px; }", "wrong-separator"),
+            (
+                "Replace comma with space".to_string(),
+                ".a { margin: 10px 5px; }".to_string()
+            )
+        );
+        assert_eq!(
+            fix(".a { transition: opacity,0.3s; }", "wrong-separator").1,
+            ".a { transition: opacity 0.3s; }"
+        );
+    }
+
+    #[test]
+    fn space_replaced_with_comma() {
+        assert_eq!(
+            fix(
+                ".a { transition-property: opacity   translate; }",
+                "wrong-separator"
+            ),
+            (
+                "Replace space with comma".to_string(),
+                ".a { transition-property: opacity, translate; }".to_string()
+            )
+        );
+        assert_eq!(
+            fix(".a { transition: opacity scale 1s; }", "wrong-separator").1,
+            ".a { transition: opacity, scale 1s; }"
+        );
+    }
+
+    #[test]
+    fn separators_fixed_one_at_a_time() {
+        let fixes = fixes(".a { padding: 1px, 2px, 3px; }", "wrong-separator");
+        let results: Vec<&str> = fixes.iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(
+            results,
+            [
+                ".a { padding: 1px 2px, 3px; }",
+                ".a { padding: 1px, 2px 3px; }"
+            ]
+        );
+    }
+
+    #[test]
+    fn semicolon_inserted_after_value() {
+        assert_eq!(
+            fix(
+                ".a {\n    width: 100px /* wide */\n    height: 50px;\n}\n",
+                "missing-semicolon"
+            ),
+            (
+                "Add semicolon".to_string(),
+                ".a {\n    width: 100px; /* wide */\n    height: 50px;\n}\n".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn pseudo_class_separators_fixed() {
+        for (text, code, title, fixed) in [
+            (
+                "Button: hover { color: red; }",
+                "pseudo-class-space",
+                "Remove space after ':'",
+                "Button:hover { color: red; }",
+            ),
+            (
+                "Button::hover { color: red; }",
+                "pseudo-class-double-colon",
+                "Use a single colon",
+                "Button:hover { color: red; }",
+            ),
+            (
+                "Button;hover { color: red; }",
+                "pseudo-class-semicolon",
+                "Replace ';' with ':'",
+                "Button:hover { color: red; }",
+            ),
+            (
+                ".a:  focus, .b { }",
+                "pseudo-class-space",
+                "Remove space after ':'",
+                ".a:focus, .b { }",
+            ),
+        ] {
+            assert_eq!(
+                fix(text, code),
+                (title.to_string(), fixed.to_string()),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn identical_rules_merged_into_the_first() {
+        let (title, fixed) = fix(
+            ".a {\n    color: red;\n}\n\n.b {\n    margin: 0;\n}\n\n.c {\n    color:  red;\n}\n",
+            "identical-rule-body",
+        );
+        assert_eq!(title, "Merge with identical rules");
+        assert_eq!(
+            fixed,
+            ".a, .c {\n    color: red;\n}\n\n.b {\n    margin: 0;\n}\n"
+        );
+    }
+
+    #[test]
+    fn identical_rules_not_merged_across_the_same_selector() {
+        // Moving `.c`'s color above the middle `.c` rule would change the cascade
+        let text =
+            ".a {\n    color: red;\n}\n.c {\n    color: blue;\n}\n.c {\n    color: red;\n}\n";
+        assert!(fixes(text, "identical-rule-body").is_empty());
+    }
+
+    #[test]
+    fn shadowed_declaration_removed() {
+        let (title, fixed) = fix(
+            ".a {\n    color: red;\n    margin: 0;\n}\n.a { color: blue; }\n",
+            "shadowed-declaration",
+        );
+        assert_eq!(title, "Remove overridden declaration");
+        assert_eq!(fixed, ".a {\n    margin: 0;\n}\n.a { color: blue; }\n");
+
+        let (_, fixed) = fix(
+            ".a { color: red; margin: 0; }\n.a { color: blue; }\n",
+            "shadowed-declaration",
+        );
+        assert_eq!(fixed, ".a { margin: 0; }\n.a { color: blue; }\n");
+    }
+
+    #[test]
+    fn variable_definition_moved_above_usage() {
+        let (title, fixed) = fix(
+            ".a {\n    color: var(--accent);\n    margin: 0;\n    --accent: red;\n}\n",
+            "variable-used-before-definition",
+        );
+        assert_eq!(title, "Move '--accent' definition above its usage");
+        assert_eq!(
+            fixed,
+            ".a {\n    --accent: red;\n    color: var(--accent);\n    margin: 0;\n}\n"
+        );
+
+        let (_, fixed) = fix(
+            ".a { color: var(--accent); --accent: red; }\n",
+            "variable-used-before-definition",
+        );
+        assert_eq!(fixed, ".a { --accent: red; color: var(--accent); }\n");
+    }
+
+    #[test]
+    fn companion_declarations_inserted() {
+        for rule in COMPANION_RULES {
+            for property in rule.properties {
+                let (title, fixed) = fix(&format!(".a {{\n    {property}: 1;\n}}\n"), rule.code);
+                assert_eq!(title, format!("Add {} declaration", rule.companion));
+                assert_eq!(
+                    fixed,
+                    format!(".a {{\n    {}\n    {property}: 1;\n}}\n", rule.stub)
+                );
+
+                let (_, fixed) = fix(&format!(".a {{ {property}: 1; }}\n"), rule.code);
+                assert_eq!(fixed, format!(".a {{ {} {property}: 1; }}\n", rule.stub));
+            }
+        }
+    }
+
+    #[test]
+    fn repeated_literal_replaced_with_new_root_variable() {
+        let refactorings = refactorings(
+            ".a {\n    color: #FF|0000;\n}\n\
+             .b { border-color: #ff0000; background-color: #f00; }\n\
+             .c {\n    --accent: #FF0000;\n    -unity-background-image-tint-color: var(--tint, #FF0000);\n    border-top-color: #FF0000; /* #FF0000 */\n}\n",
+        );
+        assert_eq!(
+            refactorings,
+            [(
+                "Replace all 3 occurrences of #FF0000 with a new variable".to_string(),
+                ":root {\n    --color-ff0000: #FF0000;\n}\n\n\
+                 .a {\n    color: var(--color-ff0000);\n}\n\
+                 .b { border-color: var(--color-ff0000); background-color: #f00; }\n\
+                 .c {\n    --accent: #FF0000;\n    -unity-background-image-tint-color: var(--tint, #FF0000);\n    border-top-color: var(--color-ff0000); /* #FF0000 */\n}\n"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn repeated_literal_defined_in_existing_root() {
+        let refactorings = refactorings(
+            ".a { margin: 4px; }\n\
+             :root {\n\t--size-4px: 4px;\n}\n\
+             .b {\n\tpadding: 0 4px;\n\tborder-width: 4|px;\n}\n",
+        );
+        assert_eq!(
+            refactorings,
+            [(
+                "Replace all 3 occurrences of 4px with a new variable".to_string(),
+                ".a { margin: var(--size-4px-2); }\n\
+                 :root {\n\t--size-4px-2: 4px;\n\t--size-4px: 4px;\n}\n\
+                 .b {\n\tpadding: 0 var(--size-4px-2);\n\tborder-width: var(--size-4px-2);\n}\n"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn literal_repeated_twice_not_replaced() {
+        assert!(refactorings(".a { margin: 4|px; }\n.b { padding: 4px; }\n").is_empty());
+    }
+}
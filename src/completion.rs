@@ -2,15 +2,25 @@
 //!
 //! Provides intelligent code completion for USS properties, values, selectors, and more.
 
-use crate::document::Document;
-use crate::uss_data::{USS_COLORS, USS_PROPERTIES, USS_PSEUDO_CLASSES, USS_UNITS, UXML_ELEMENTS};
-use tower_lsp::lsp_types::*;
+use crate::document::{defined_variables, Document};
+use crate::parser::parse;
+use crate::settings::{ScaleSettings, Settings, TargetContext};
+use crate::tokenizer::{brace_delta, tokenize, TokenKind};
+use crate::uss_data::{
+    UNIVERSAL_SELECTOR_DESCRIPTION, USS_BEST_PRACTICES_URL, USS_COLORS, USS_PROPERTIES,
+    USS_PSEUDO_CLASSES, USS_UNITS, UXML_ELEMENTS,
+};
+use crate::values::{split_components, transform_origin_slot_keywords, TIMING_FUNCTIONS};
+use lsp_types::*;
+use std::collections::HashSet;
 
 /// Context for completion
 #[derive(Debug, Clone, PartialEq)]
 enum CompletionContext {
     /// At the start of a selector
     Selector,
+    /// After a comma in a selector list, before the opening brace
+    SelectorList,
     /// After a class selector dot
     ClassSelector,
     /// After an ID selector hash
@@ -31,17 +41,11 @@ enum CompletionContext {
 
 /// Determine the completion context based on the cursor position
 fn get_completion_context(doc: &Document, position: Position) -> CompletionContext {
-    let line = match doc.get_line(position.line) {
-        Some(l) => l,
+    let text_before = match doc.get_text_before_cursor(position) {
+        Some(t) => t,
         None => return CompletionContext::Unknown,
     };
-
-    let col = position.character as usize;
-    let text_before = if col > line.len() {
-        &line
-    } else {
-        &line[..col]
-    };
+    let text_before = text_before.as_str();
 
     // Check if we're in a var() function
     if text_before.contains("var(") && !text_before.contains(')') {
@@ -99,13 +103,10 @@ fn get_completion_context(doc: &Document, position: Position) -> CompletionConte
 
     // Check if we're inside a declaration block
     let full_text = doc.get_text();
-    let offset = doc.position_to_offset(position).unwrap_or(0);
+    let offset = doc.position_to_byte_offset(position).unwrap_or(0);
     let text_before_full = &full_text[..offset];
 
-    let open_braces = text_before_full.matches('{').count();
-    let close_braces = text_before_full.matches('}').count();
-
-    if open_braces > close_braces {
+    if brace_delta(&tokenize(text_before_full)) > 0 {
         // We're inside a declaration block
 
         // Check if we're after a property colon (expecting value)
@@ -116,6 +117,15 @@ fn get_completion_context(doc: &Document, position: Position) -> CompletionConte
             }
         }
 
+        // Check if we're on a continuation line of a wrapped value
+        if let Some((prop_name, value)) = wrapped_declaration(doc, position) {
+            return match open_function(&value).as_deref() {
+                Some("var") => CompletionContext::Variable,
+                Some("url" | "resource") => CompletionContext::Url,
+                _ => CompletionContext::PropertyValue(prop_name),
+            };
+        }
+
         // Check if we're at the start of a new property
         let trimmed = text_before.trim();
         if trimmed.is_empty()
@@ -129,46 +139,221 @@ fn get_completion_context(doc: &Document, position: Position) -> CompletionConte
         }
     } else {
         // We're outside declaration blocks - in selector context
+        if is_selector_list_continuation(text_before_full) {
+            return CompletionContext::SelectorList;
+        }
         return CompletionContext::Selector;
     }
 
     CompletionContext::Unknown
 }
 
+/// Check whether the text before the cursor continues a comma-separated selector list
+///
+/// The identifier currently being typed is ignored, so both `.a, |` and `.a, Bu|`
+/// (including lists spanning several lines) are recognized.
+fn is_selector_list_continuation(text_before: &str) -> bool {
+    let without_word =
+        text_before.trim_end_matches(|c: char| c.is_alphanumeric() || c == '-' || c == '_');
+    without_word.trim_end().ends_with(',')
+}
+
+/// Get the part of the declaration value before the cursor
+///
+/// On a continuation line of a wrapped value this includes the lines above.
+fn value_before_cursor(doc: &Document, position: Position) -> String {
+    let before = doc.get_text_before_cursor(position).unwrap_or_default();
+    match before.split_once(':') {
+        Some((_, value)) => value.to_string(),
+        None => wrapped_declaration(doc, position)
+            .map(|(_, value)| value)
+            .unwrap_or_default(),
+    }
+}
+
+/// Find the declaration whose value wraps onto the cursor's line
+///
+/// Returns the property and the value up to the cursor. The value is open at
+/// the start of the line when it is still empty, ends with a comma, or has an
+/// unclosed parenthesis, or when it runs on to a semicolon after the cursor.
+///
+/// Only the lines around the cursor are tokenized: from the nearest line above
+/// ending the previous declaration or opening the rule, to the nearest line
+/// below ending this declaration.
+fn wrapped_declaration(doc: &Document, position: Position) -> Option<(String, String)> {
+    let is_boundary_line = |line: u32| {
+        doc.get_line(line)
+            .is_some_and(|text| text.contains(['{', '}', ';']))
+    };
+    let last_line = (position.line..doc.line_count() as u32)
+        .find(|&line| is_boundary_line(line))
+        .unwrap_or(position.line);
+    let end = doc.content.line_to_byte(last_line as usize + 1);
+
+    // Widen the window upwards until it contains the declaration's start; a
+    // boundary character may be inside a comment or string
+    let mut first_line = position.line;
+    loop {
+        first_line = (0..first_line).rev().find(|&line| is_boundary_line(line))?;
+        let start = doc.content.line_to_byte(first_line as usize);
+        let window = doc.content.byte_slice(start..end).to_string();
+        let offset = doc.position_to_byte_offset(position)? - start;
+        let line_start = doc.content.line_to_byte(position.line as usize) - start;
+        if let Some(found) = wrapped_in_window(&window, offset, line_start) {
+            return found;
+        }
+    }
+}
+
+/// Find the declaration wrapping onto the line at `line_start` in a window
+///
+/// Returns `None` when the window does not reach back to the start of the
+/// declaration, and `Some(None)` when the line does not continue a value.
+fn wrapped_in_window(
+    window: &str,
+    offset: usize,
+    line_start: usize,
+) -> Option<Option<(String, String)>> {
+    let tokens = tokenize(window);
+    let significant: Vec<_> = tokens
+        .iter()
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .collect();
+    let is_boundary = |kind| matches!(kind, TokenKind::Delim('{' | '}' | ';'));
+
+    let boundary = significant
+        .iter()
+        .rposition(|t| t.start < line_start && is_boundary(t.kind))?;
+    let declaration = &significant[boundary + 1..];
+    let Some(colon) = declaration
+        .windows(2)
+        .find(|pair| pair[0].kind == TokenKind::Ident && pair[1].kind == TokenKind::Delim(':'))
+    else {
+        return Some(None);
+    };
+    let (property, colon) = (colon[0].text(window), colon[1].end);
+    if colon > line_start || colon > offset {
+        return Some(None);
+    }
+
+    // Comments between the value and the line are ignored
+    let before_line = window[colon..line_start].trim_end();
+    let last = significant.iter().rfind(|t| t.start < line_start)?;
+    let runs_on = significant
+        .iter()
+        .find(|t| t.start >= offset && matches!(t.kind, TokenKind::Delim('{' | '}' | ';' | ':')))
+        .is_some_and(|t| t.kind == TokenKind::Delim(';'));
+    let open = last.end == colon
+        || last.kind == TokenKind::Delim(',')
+        || open_function(before_line).is_some()
+        || runs_on;
+    Some(open.then(|| (property.to_string(), window[colon..offset].to_string())))
+}
+
+/// Name of the innermost function call left open in a value
+///
+/// Returns an empty name for parentheses that do not belong to a function.
+fn open_function(value: &str) -> Option<String> {
+    let tokens = tokenize(value);
+    let mut open = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::Delim('(') => {
+                let name = match i.checked_sub(1).map(|i| tokens[i]) {
+                    Some(prev) if prev.kind == TokenKind::Ident && prev.end == token.start => {
+                        prev.text(value).to_ascii_lowercase()
+                    }
+                    _ => String::new(),
+                };
+                open.push(name);
+            }
+            TokenKind::Delim(')') => {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+    open.pop()
+}
+
+/// Check whether the rest of the cursor's line already opens a declaration block
+fn has_block_after_cursor(doc: &Document, position: Position) -> bool {
+    let (Some(line), Some(before)) = (
+        doc.get_line(position.line),
+        doc.get_text_before_cursor(position),
+    ) else {
+        return false;
+    };
+    line[before.len()..].contains('{')
+}
+
 /// Get completions based on the current context
-pub fn get_completions(doc: &Document, position: Position) -> Vec<CompletionItem> {
+///
+/// `uxml_classes` holds the classes assigned in the workspace's UXML files,
+/// offered after the document's own classes.
+pub fn get_completions(
+    doc: &Document,
+    position: Position,
+    settings: &Settings,
+    workspace_variables: &HashSet<String>,
+    uxml_classes: &HashSet<String>,
+) -> Vec<CompletionItem> {
     let context = get_completion_context(doc, position);
 
     match context {
-        CompletionContext::Selector => get_selector_completions(),
-        CompletionContext::ClassSelector => get_class_selector_completions(doc),
+        CompletionContext::Selector => get_selector_completions(
+            has_block_after_cursor(doc, position),
+            settings.target_context,
+        ),
+        CompletionContext::SelectorList => {
+            get_selector_list_completions(doc, settings.target_context)
+        }
+        CompletionContext::ClassSelector => get_class_selector_completions(doc, uxml_classes),
         CompletionContext::IdSelector => get_id_selector_completions(doc),
         CompletionContext::PseudoClass => get_pseudo_class_completions(),
-        CompletionContext::PropertyName => get_property_name_completions(),
-        CompletionContext::PropertyValue(prop) => get_property_value_completions(&prop),
+        CompletionContext::PropertyName => get_property_name_completions(
+            &declared_properties(doc, position),
+            settings.completion.hide_declared_properties,
+        ),
+        CompletionContext::PropertyValue(prop) => {
+            let values = get_property_value_completions(&prop, &value_before_cursor(doc, position));
+            with_scale_completions(&prop, &settings.scales, values)
+        }
         CompletionContext::Url => get_url_completions(),
-        CompletionContext::Variable => get_variable_completions(doc),
+        CompletionContext::Variable => get_variable_completions(doc, workspace_variables),
         CompletionContext::Unknown => vec![],
     }
 }
 
 /// Get selector completions (element types)
-fn get_selector_completions() -> Vec<CompletionItem> {
+///
+/// When the line already contains an opening brace after the cursor, element
+/// items insert only their name so the block is not inserted twice.
+fn get_selector_completions(has_block: bool, target: TargetContext) -> Vec<CompletionItem> {
     let mut items = Vec::new();
 
-    // Add UXML element types
-    for elem in UXML_ELEMENTS.iter() {
+    // Add UXML element types available in the target context
+    for elem in UXML_ELEMENTS.iter().filter(|e| target.allows_element(e)) {
+        let insert_text = if has_block {
+            elem.name.to_string()
+        } else {
+            format!("{} {{\n    $0\n}}", elem.name)
+        };
         items.push(CompletionItem {
             label: elem.name.to_string(),
             kind: Some(CompletionItemKind::CLASS),
             detail: Some(elem.namespace.to_string()),
             documentation: Some(Documentation::String(elem.description.to_string())),
-            insert_text: Some(format!("{} {{\n    $0\n}}", elem.name)),
+            insert_text: Some(insert_text),
             insert_text_format: Some(InsertTextFormat::SNIPPET),
             ..Default::default()
         });
     }
 
+    if has_block {
+        return items;
+    }
+
     // Add common selector prefixes
     items.push(CompletionItem {
         label: ".".to_string(),
@@ -192,6 +377,7 @@ fn get_selector_completions() -> Vec<CompletionItem> {
         label: "*".to_string(),
         kind: Some(CompletionItemKind::SNIPPET),
         detail: Some("Universal selector".to_string()),
+        documentation: Some(universal_selector_documentation()),
         insert_text: Some("* {\n    $0\n}".to_string()),
         insert_text_format: Some(InsertTextFormat::SNIPPET),
         ..Default::default()
@@ -200,10 +386,67 @@ fn get_selector_completions() -> Vec<CompletionItem> {
     items
 }
 
-/// Get class selector completions from the document
-fn get_class_selector_completions(doc: &Document) -> Vec<CompletionItem> {
+/// Documentation of the universal selector, warning about its matching cost
+fn universal_selector_documentation() -> Documentation {
+    Documentation::MarkupContent(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: format!(
+            "⚠️ **Performance:** {}\n\n[USS best practices]({})",
+            UNIVERSAL_SELECTOR_DESCRIPTION, USS_BEST_PRACTICES_URL
+        ),
+    })
+}
+
+/// Get selector list completions (after a comma, before the opening brace)
+///
+/// Offers element types, existing classes with their leading dot and existing
+/// IDs with their hash, all without the block snippet.
+fn get_selector_list_completions(doc: &Document, target: TargetContext) -> Vec<CompletionItem> {
+    let mut items = Vec::new();
+
+    for elem in UXML_ELEMENTS.iter().filter(|e| target.allows_element(e)) {
+        items.push(CompletionItem {
+            label: elem.name.to_string(),
+            kind: Some(CompletionItemKind::CLASS),
+            detail: Some(elem.namespace.to_string()),
+            documentation: Some(Documentation::String(elem.description.to_string())),
+            ..Default::default()
+        });
+    }
+
+    for class in collect_document_classes(doc) {
+        items.push(CompletionItem {
+            label: format!(".{}", class),
+            kind: Some(CompletionItemKind::CLASS),
+            detail: Some("Class selector".to_string()),
+            ..Default::default()
+        });
+    }
+
+    for id in collect_document_ids(doc) {
+        items.push(CompletionItem {
+            label: format!("#{}", id),
+            kind: Some(CompletionItemKind::REFERENCE),
+            detail: Some("ID selector".to_string()),
+            ..Default::default()
+        });
+    }
+
+    items.push(CompletionItem {
+        label: "*".to_string(),
+        kind: Some(CompletionItemKind::OPERATOR),
+        detail: Some("Universal selector".to_string()),
+        documentation: Some(universal_selector_documentation()),
+        ..Default::default()
+    });
+
+    items
+}
+
+/// Collect all class names used in the document
+fn collect_document_classes(doc: &Document) -> HashSet<String> {
     let text = doc.get_text();
-    let mut classes = std::collections::HashSet::new();
+    let mut classes = HashSet::new();
 
     // Find all class selectors in the document
     let re = regex::Regex::new(r"\.([a-zA-Z_][\w-]*)").unwrap();
@@ -214,20 +457,12 @@ fn get_class_selector_completions(doc: &Document) -> Vec<CompletionItem> {
     }
 
     classes
-        .into_iter()
-        .map(|class| CompletionItem {
-            label: class.clone(),
-            kind: Some(CompletionItemKind::CLASS),
-            detail: Some("Class selector".to_string()),
-            ..Default::default()
-        })
-        .collect()
 }
 
-/// Get ID selector completions from the document
-fn get_id_selector_completions(doc: &Document) -> Vec<CompletionItem> {
+/// Collect all ID names used in the document
+fn collect_document_ids(doc: &Document) -> HashSet<String> {
     let text = doc.get_text();
-    let mut ids = std::collections::HashSet::new();
+    let mut ids = HashSet::new();
 
     // Find all ID selectors in the document
     let re = regex::Regex::new(r"#([a-zA-Z_][\w-]*)").unwrap();
@@ -237,7 +472,43 @@ fn get_id_selector_completions(doc: &Document) -> Vec<CompletionItem> {
         }
     }
 
-    ids.into_iter()
+    ids
+}
+
+/// Get class selector completions from the document, then from UXML files
+fn get_class_selector_completions(
+    doc: &Document,
+    uxml_classes: &HashSet<String>,
+) -> Vec<CompletionItem> {
+    let classes = collect_document_classes(doc);
+    let mut items: Vec<CompletionItem> = classes
+        .iter()
+        .map(|class| CompletionItem {
+            label: class.clone(),
+            kind: Some(CompletionItemKind::CLASS),
+            detail: Some("Class selector".to_string()),
+            sort_text: Some(format!("0{}", class)),
+            ..Default::default()
+        })
+        .collect();
+
+    for class in uxml_classes.difference(&classes) {
+        items.push(CompletionItem {
+            label: class.clone(),
+            kind: Some(CompletionItemKind::CLASS),
+            detail: Some("Class used in UXML".to_string()),
+            sort_text: Some(format!("1{}", class)),
+            ..Default::default()
+        });
+    }
+
+    items
+}
+
+/// Get ID selector completions from the document
+fn get_id_selector_completions(doc: &Document) -> Vec<CompletionItem> {
+    collect_document_ids(doc)
+        .into_iter()
         .map(|id| CompletionItem {
             label: id.clone(),
             kind: Some(CompletionItemKind::REFERENCE),
@@ -261,12 +532,45 @@ fn get_pseudo_class_completions() -> Vec<CompletionItem> {
         .collect()
 }
 
+/// Properties declared in the rule enclosing a position
+///
+/// The declaration whose name is being typed at the position is not counted.
+fn declared_properties(doc: &Document, position: Position) -> HashSet<String> {
+    let text = doc.get_text();
+    let Some(offset) = doc.position_to_byte_offset(position) else {
+        return HashSet::new();
+    };
+    let stylesheet = parse(&text);
+    let Some(rule) = stylesheet.rule_at(offset) else {
+        return HashSet::new();
+    };
+
+    rule.declarations
+        .iter()
+        .filter(|d| !(d.property_start <= offset && offset <= d.property_end))
+        .map(|d| d.property.clone())
+        .collect()
+}
+
 /// Get property name completions
-fn get_property_name_completions() -> Vec<CompletionItem> {
+///
+/// Properties already declared in the rule are listed after the others, or
+/// omitted when `hide_declared` is set. Only exact names count, so declaring a
+/// shorthand such as `margin` does not affect `margin-top`.
+fn get_property_name_completions(
+    declared: &HashSet<String>,
+    hide_declared: bool,
+) -> Vec<CompletionItem> {
     USS_PROPERTIES
         .iter()
+        .filter(|(name, _)| !(hide_declared && declared.contains(**name)))
         .map(|(name, prop)| CompletionItem {
             label: name.to_string(),
+            sort_text: Some(format!(
+                "{}{}",
+                if declared.contains(*name) { 1 } else { 0 },
+                name
+            )),
             kind: Some(CompletionItemKind::PROPERTY),
             detail: Some(prop.syntax.to_string()),
             documentation: Some(Documentation::MarkupContent(MarkupContent {
@@ -286,14 +590,39 @@ fn get_property_name_completions() -> Vec<CompletionItem> {
 }
 
 /// Get property value completions
-fn get_property_value_completions(property_name: &str) -> Vec<CompletionItem> {
+fn get_property_value_completions(property_name: &str, value_before: &str) -> Vec<CompletionItem> {
+    if property_name == "transform-origin" {
+        return get_transform_origin_completions(value_before);
+    }
+    if property_name == "transition" || property_name == "transition-property" {
+        return get_transition_completions(property_name, value_before);
+    }
+
     let mut items = Vec::new();
 
     // Get property-specific values
     if let Some(prop) = USS_PROPERTIES.get(property_name) {
-        for value in &prop.values {
+        for (index, value) in prop.values.iter().enumerate() {
+            // Grouped keywords sort by category, then in the category's order;
+            // ungrouped ones follow in table order
+            let group =
+                prop.value_groups
+                    .iter()
+                    .enumerate()
+                    .find_map(|(group, (name, keywords))| {
+                        let position = keywords.iter().position(|k| k == value)?;
+                        Some((group, position, *name))
+                    });
+            let (group_index, position) =
+                group.map_or((prop.value_groups.len(), index), |(g, p, _)| (g, p));
             items.push(CompletionItem {
                 label: value.to_string(),
+                label_details: group.map(|(_, _, name)| CompletionItemLabelDetails {
+                    detail: None,
+                    description: Some(name.to_string()),
+                }),
+                sort_text: (!prop.value_groups.is_empty())
+                    .then(|| format!("{:02}{:03}", group_index, position)),
                 kind: Some(CompletionItemKind::VALUE),
                 detail: Some(format!("Value for {}", property_name)),
                 ..Default::default()
@@ -404,22 +733,179 @@ fn get_property_value_completions(property_name: &str) -> Vec<CompletionItem> {
 
     // Add time units for transitions
     if property_name.contains("duration") || property_name.contains("delay") {
-        items.push(CompletionItem {
+        items.extend(get_time_unit_completions());
+    }
+
+    items
+}
+
+/// Get `0s` and `0ms` time unit completions
+fn get_time_unit_completions() -> Vec<CompletionItem> {
+    vec![
+        CompletionItem {
             label: "0s".to_string(),
             kind: Some(CompletionItemKind::UNIT),
             detail: Some("Seconds".to_string()),
             insert_text: Some("${1:0}s".to_string()),
             insert_text_format: Some(InsertTextFormat::SNIPPET),
             ..Default::default()
-        });
-        items.push(CompletionItem {
+        },
+        CompletionItem {
             label: "0ms".to_string(),
             kind: Some(CompletionItemKind::UNIT),
             detail: Some("Milliseconds".to_string()),
             insert_text: Some("${1:0}ms".to_string()),
             insert_text_format: Some(InsertTextFormat::SNIPPET),
             ..Default::default()
-        });
+        },
+    ]
+}
+
+/// Get list-aware `transition` and `transition-property` completions
+///
+/// Every item of `transition-property`, and the first component of each
+/// transition in the `transition` shorthand, is a property name; later
+/// components of a transition take times and timing functions. `all` and
+/// `none` are only offered where they are valid.
+fn get_transition_completions(property_name: &str, value_before: &str) -> Vec<CompletionItem> {
+    let item_start = last_top_level_comma(value_before).map_or(0, |comma| comma + 1);
+    let item = &value_before[item_start..];
+    let mut previous = split_components(item);
+    if !item.ends_with(char::is_whitespace) {
+        // The last component is still being typed
+        previous.pop();
+    }
+
+    let mut items = Vec::new();
+    if property_name == "transition-property" || previous.is_empty() {
+        let keywords: &[&str] = match (item_start, property_name) {
+            (0, _) => &["all", "none"],
+            (_, "transition") => &["all"],
+            _ => &[],
+        };
+        items.extend(keywords.iter().map(|keyword| CompletionItem {
+            label: keyword.to_string(),
+            sort_text: Some(format!("0{}", keyword)),
+            kind: Some(CompletionItemKind::VALUE),
+            detail: Some(format!("Value for {}", property_name)),
+            ..Default::default()
+        }));
+        items.extend(
+            USS_PROPERTIES
+                .keys()
+                .filter(|name| !name.starts_with("transition"))
+                .map(|name| CompletionItem {
+                    label: name.to_string(),
+                    sort_text: Some(format!("1{}", name)),
+                    kind: Some(CompletionItemKind::PROPERTY),
+                    detail: Some("Property to transition".to_string()),
+                    ..Default::default()
+                }),
+        );
+    } else {
+        items.extend(TIMING_FUNCTIONS.iter().map(|function| CompletionItem {
+            label: function.to_string(),
+            kind: Some(CompletionItemKind::VALUE),
+            detail: Some("Timing function".to_string()),
+            ..Default::default()
+        }));
+        items.extend(get_time_unit_completions());
+    }
+
+    items.push(CompletionItem {
+        label: "var()".to_string(),
+        kind: Some(CompletionItemKind::FUNCTION),
+        insert_text: Some("var(--$1)".to_string()),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        ..Default::default()
+    });
+    items
+}
+
+/// Byte offset of the last comma outside parentheses in a value
+fn last_top_level_comma(value: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut comma = None;
+    for token in tokenize(value) {
+        match token.kind {
+            TokenKind::Delim('(') => depth += 1,
+            TokenKind::Delim(')') if depth > 0 => depth -= 1,
+            TokenKind::Delim(',') if depth == 0 => comma = Some(token.start),
+            _ => {}
+        }
+    }
+    comma
+}
+
+/// Add the configured design scale of a property ahead of its other values
+///
+/// Without a scale for the property the values are returned unchanged.
+fn with_scale_completions(
+    property_name: &str,
+    scales: &ScaleSettings,
+    values: Vec<CompletionItem>,
+) -> Vec<CompletionItem> {
+    let Some((scale, name)) = scales.for_property(property_name) else {
+        return values;
+    };
+
+    let mut items: Vec<CompletionItem> = scale
+        .iter()
+        .enumerate()
+        .map(|(index, value)| CompletionItem {
+            label: format!("{}px", value),
+            sort_text: Some(format!("0{:04}", index)),
+            kind: Some(CompletionItemKind::VALUE),
+            detail: Some(name.to_string()),
+            ..Default::default()
+        })
+        .collect();
+    items.extend(values.into_iter().map(|mut item| {
+        item.sort_text = Some(format!(
+            "1{}",
+            item.sort_text.as_deref().unwrap_or(&item.label)
+        ));
+        item
+    }));
+    items
+}
+
+/// Get slot-aware transform-origin completions
+///
+/// The first slot offers every keyword; the second offers only keywords for
+/// the axis the first value left unset.
+fn get_transform_origin_completions(value_before: &str) -> Vec<CompletionItem> {
+    let mut previous = split_components(value_before);
+    if !value_before.ends_with(char::is_whitespace) {
+        // The last component is still being typed
+        previous.pop();
+    }
+
+    let mut items: Vec<CompletionItem> = transform_origin_slot_keywords(&previous)
+        .iter()
+        .map(|keyword| CompletionItem {
+            label: keyword.to_string(),
+            kind: Some(CompletionItemKind::VALUE),
+            detail: Some(if previous.is_empty() {
+                "Origin (x, or y for top/bottom)".to_string()
+            } else {
+                "Origin, second value".to_string()
+            }),
+            ..Default::default()
+        })
+        .collect();
+
+    if previous.len() < 2 {
+        for (unit, desc) in USS_UNITS.iter().filter(|(u, _)| *u == "px" || *u == "%") {
+            items.push(CompletionItem {
+                label: format!("0{}", unit),
+                kind: Some(CompletionItemKind::UNIT),
+                detail: Some(desc.to_string()),
+                insert_text: Some(format!("${{1:0}}{}", unit)),
+                insert_text_format: Some(InsertTextFormat::SNIPPET),
+                ..Default::default()
+            });
+        }
     }
 
     items
@@ -446,26 +932,37 @@ fn get_url_completions() -> Vec<CompletionItem> {
 }
 
 /// Get variable completions from the document
-fn get_variable_completions(doc: &Document) -> Vec<CompletionItem> {
-    let text = doc.get_text();
-    let mut vars = std::collections::HashSet::new();
-
-    // Find all variable definitions (--var-name: value;)
-    let re = regex::Regex::new(r"(--[\w-]+)\s*:").unwrap();
-    for cap in re.captures_iter(&text) {
-        if let Some(m) = cap.get(1) {
-            vars.insert(m.as_str().to_string());
-        }
-    }
+///
+/// Variables defined in this document come first, followed by variables defined
+/// elsewhere in the workspace.
+fn get_variable_completions(
+    doc: &Document,
+    workspace_variables: &HashSet<String>,
+) -> Vec<CompletionItem> {
+    let local = defined_variables(&doc.get_text());
 
-    vars.into_iter()
+    let mut items: Vec<CompletionItem> = local
+        .iter()
         .map(|var| CompletionItem {
             label: var.clone(),
             kind: Some(CompletionItemKind::VARIABLE),
             detail: Some("USS variable".to_string()),
             ..Default::default()
         })
-        .collect()
+        .collect();
+
+    items.extend(
+        workspace_variables
+            .difference(&local)
+            .map(|var| CompletionItem {
+                label: var.clone(),
+                kind: Some(CompletionItemKind::VARIABLE),
+                detail: Some("USS variable (workspace)".to_string()),
+                ..Default::default()
+            }),
+    );
+
+    items
 }
 
 /// Resolve additional completion item details
@@ -502,3 +999,431 @@ pub fn resolve_completion(mut item: CompletionItem) -> CompletionItem {
 
     item
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Document and cursor position for text with a `|` marking the cursor
+    fn at_cursor(text: &str) -> (Document, Position) {
+        let offset = text.find('|').unwrap();
+        let doc = Document::new(text.replacen('|', "", 1), 1);
+        let position = doc.byte_offset_to_position(offset);
+        (doc, position)
+    }
+
+    fn context(text: &str) -> CompletionContext {
+        let (doc, position) = at_cursor(text);
+        get_completion_context(&doc, position)
+    }
+
+    fn completions(text: &str) -> Vec<CompletionItem> {
+        completions_with(text, &Settings::default())
+    }
+
+    fn completions_with(text: &str, settings: &Settings) -> Vec<CompletionItem> {
+        let (doc, position) = at_cursor(text);
+        get_completions(&doc, position, settings, &HashSet::new(), &HashSet::new())
+    }
+
+    /// Labels in the order a client shows them
+    fn sorted_labels(mut items: Vec<CompletionItem>) -> Vec<String> {
+        items.sort_by(|a, b| {
+            let key = |item: &CompletionItem| item.sort_text.clone().unwrap_or(item.label.clone());
+            key(a).cmp(&key(b))
+        });
+        items.into_iter().map(|item| item.label).collect()
+    }
+
+    fn value(property: &str) -> CompletionContext {
+        CompletionContext::PropertyValue(property.to_string())
+    }
+
+    #[test]
+    fn continuation_of_wrapped_transition() {
+        assert_eq!(
+            context(".a {\n    transition: opacity 0.3s,\n        |\n}\n"),
+            value("transition")
+        );
+        assert_eq!(
+            context(
+                ".a {\n    color: red;\n    transition:\n        opacity 0.3s,\n        |\n}\n"
+            ),
+            value("transition")
+        );
+    }
+
+    #[test]
+    fn continuation_of_wrapped_font_declaration() {
+        assert_eq!(
+            context(".a {\n    -unity-font-style:\n        |\n}\n"),
+            value("-unity-font-style")
+        );
+        assert_eq!(context(".a { font-size:\n    |\n}\n"), value("font-size"));
+    }
+
+    #[test]
+    fn continuation_running_on_to_a_semicolon() {
+        assert_eq!(
+            context(".a {\n    transition: opacity 0.3s\n        |ease-in;\n}\n"),
+            value("transition")
+        );
+    }
+
+    #[test]
+    fn continuation_across_comments_with_boundaries() {
+        assert_eq!(
+            context(".a {\n    transition: opacity 0.3s, /* fast; } */\n        |\n}\n"),
+            value("transition")
+        );
+    }
+
+    #[test]
+    fn new_declaration_after_a_complete_one() {
+        assert_eq!(
+            context(".a {\n    transition: opacity 0.3s;\n    |\n}\n"),
+            CompletionContext::PropertyName
+        );
+        assert_eq!(
+            context(".a {\n    -unity-font-style: bold\n    |\n}\n"),
+            CompletionContext::PropertyName
+        );
+    }
+
+    #[test]
+    fn wrapped_value_before_cursor() {
+        let (doc, position) =
+            at_cursor(".a {\n    transition: opacity 0.3s,\n        scale |\n}\n");
+        assert_eq!(
+            value_before_cursor(&doc, position),
+            " opacity 0.3s,\n        scale "
+        );
+    }
+
+    #[test]
+    fn selector_list_after_comma_on_the_same_line() {
+        assert_eq!(
+            context(".primary-button, |"),
+            CompletionContext::SelectorList
+        );
+        assert_eq!(
+            context(".primary-button,|"),
+            CompletionContext::SelectorList
+        );
+        assert_eq!(context(".a, Bu|"), CompletionContext::SelectorList);
+        assert_eq!(context(".a, | {\n}\n"), CompletionContext::SelectorList);
+        assert_eq!(context(".a {}\n.b, |"), CompletionContext::SelectorList);
+    }
+
+    #[test]
+    fn selector_list_after_comma_on_the_next_line() {
+        assert_eq!(context(".a,\n|"), CompletionContext::SelectorList);
+        assert_eq!(
+            context(".a,\n    .b,\n    |"),
+            CompletionContext::SelectorList
+        );
+        assert_eq!(context(".a,\n| {\n}\n"), CompletionContext::SelectorList);
+    }
+
+    #[test]
+    fn selector_without_comma_is_not_a_list() {
+        assert_eq!(context("|"), CompletionContext::Selector);
+        assert_eq!(context(".a {}\n|"), CompletionContext::Selector);
+        assert_eq!(context(".a {\n    margin: 0, |"), value("margin"));
+    }
+
+    #[test]
+    fn selector_list_offers_elements_classes_and_ids_without_blocks() {
+        for text in [
+            ".primary-button, #main {}\n.primary-button, |",
+            ".primary-button, #main {}\n.primary-button,\n    | {\n}\n",
+        ] {
+            let items = completions(text);
+            let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+            assert!(labels.contains(&"Button"), "{:?}", labels);
+            assert!(labels.contains(&".primary-button"), "{:?}", labels);
+            assert!(labels.contains(&"#main"), "{:?}", labels);
+            assert!(
+                items
+                    .iter()
+                    .all(|item| !item.insert_text.as_deref().unwrap_or("").contains('{')),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn braces_in_strings_and_comments_do_not_change_context() {
+        assert_eq!(
+            context(".a { background-image: url(\"a{.png\"); }\n|"),
+            CompletionContext::Selector
+        );
+        assert_eq!(
+            context(".a { /* } */\n    |\n}\n"),
+            CompletionContext::PropertyName
+        );
+        assert_eq!(
+            context(".a {} .b {\n    |"),
+            CompletionContext::PropertyName
+        );
+        assert_eq!(context(".a { } .b { }\n|"), CompletionContext::Selector);
+    }
+
+    const DECLARING: &str =
+        ".a {\n    width: 10px;\n    margin: 0;\n    --gap: 4px;\n    wid|\n}\n";
+
+    #[test]
+    fn declared_properties_sort_after_the_others() {
+        let labels = sorted_labels(completions(DECLARING));
+        assert_eq!(labels.len(), USS_PROPERTIES.len());
+        assert_eq!(labels[labels.len() - 2..], ["margin", "width"]);
+
+        let position = |name: &str| labels.iter().position(|l| l == name).unwrap();
+        assert!(position("margin-top") < position("margin"));
+        assert!(position("min-width") < position("width"));
+        assert!(position("height") < position("margin"));
+    }
+
+    #[test]
+    fn declared_properties_hidden_when_configured() {
+        let mut settings = Settings::default();
+        settings.completion.hide_declared_properties = true;
+        let labels = sorted_labels(completions_with(DECLARING, &settings));
+        assert_eq!(labels.len(), USS_PROPERTIES.len() - 2);
+        assert!(!labels.iter().any(|l| l == "width" || l == "margin"));
+        assert!(labels.iter().any(|l| l == "margin-top"));
+        assert!(labels.iter().any(|l| l == "min-width"));
+    }
+
+    #[test]
+    fn property_being_typed_is_not_declared() {
+        let items = completions(".a {\n    width|: 10px;\n}\n");
+        assert_eq!(items.len(), USS_PROPERTIES.len());
+        assert!(items.iter().all(|item| item
+            .sort_text
+            .as_deref()
+            .is_some_and(|s| s.starts_with('0'))));
+    }
+
+    fn scaled() -> Settings {
+        let mut settings = Settings::default();
+        settings.scales.spacing = vec![4.0, 8.0, 12.0, 16.0, 24.0, 32.0];
+        settings.scales.font_size = vec![12.0, 14.5, 18.0];
+        settings
+    }
+
+    #[test]
+    fn spacing_scale_listed_first_in_order() {
+        for text in [
+            ".a {\n    margin: |\n}\n",
+            ".a {\n    padding-left: |\n}\n",
+            ".a {\n    margin-top: 4px |\n}\n",
+        ] {
+            let labels = sorted_labels(completions_with(text, &scaled()));
+            assert_eq!(
+                labels[..6],
+                ["4px", "8px", "12px", "16px", "24px", "32px"],
+                "{}",
+                text
+            );
+            assert!(labels.len() > 6, "{}", text);
+        }
+    }
+
+    #[test]
+    fn font_size_scale_listed_first() {
+        let items = completions_with(".a {\n    font-size: |\n}\n", &scaled());
+        let labels = sorted_labels(items.clone());
+        assert_eq!(labels[..3], ["12px", "14.5px", "18px"]);
+        assert!(items
+            .iter()
+            .filter(|item| item.detail.as_deref() == Some("Font size scale"))
+            .all(|item| item.kind == Some(CompletionItemKind::VALUE)));
+    }
+
+    #[test]
+    fn scales_unused_when_unconfigured_or_unrelated() {
+        let unconfigured = Settings::default();
+        for (text, settings) in [
+            (".a {\n    margin: |\n}\n", &unconfigured),
+            (".a {\n    font-size: |\n}\n", &unconfigured),
+            (".a {\n    width: |\n}\n", &scaled()),
+            (".a {\n    border-radius: |\n}\n", &scaled()),
+        ] {
+            let items = completions_with(text, settings);
+            assert!(
+                !items
+                    .iter()
+                    .any(|item| item.detail.as_deref().is_some_and(|d| d.ends_with("scale"))),
+                "{}",
+                text
+            );
+        }
+
+        let (doc, position) = at_cursor(".a {\n    margin: |\n}\n");
+        let values = get_property_value_completions("margin", "");
+        assert_eq!(
+            sorted_labels(get_completions(
+                &doc,
+                position,
+                &unconfigured,
+                &HashSet::new(),
+                &HashSet::new()
+            )),
+            sorted_labels(values)
+        );
+    }
+
+    /// Labels and group descriptions of a property's keywords in popup order
+    fn grouped_values(property: &str) -> Vec<(String, Option<String>)> {
+        let keywords = &USS_PROPERTIES.get(property).unwrap().values;
+        let mut items = get_property_value_completions(property, "");
+        items.sort_by_key(|item| item.sort_text.clone().unwrap_or(item.label.clone()));
+        items
+            .into_iter()
+            .filter(|item| keywords.contains(&item.label.as_str()))
+            .map(|item| {
+                let group = item.label_details.and_then(|details| details.description);
+                (item.label, group)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn text_align_grouped_upper_middle_lower() {
+        let values = grouped_values("-unity-text-align");
+        let expected = [
+            ("upper-left", "upper"),
+            ("upper-center", "upper"),
+            ("upper-right", "upper"),
+            ("middle-left", "middle"),
+            ("middle-center", "middle"),
+            ("middle-right", "middle"),
+            ("lower-left", "lower"),
+            ("lower-center", "lower"),
+            ("lower-right", "lower"),
+        ];
+        assert_eq!(
+            values,
+            expected.map(|(label, group)| (label.to_string(), Some(group.to_string())))
+        );
+    }
+
+    #[test]
+    fn cursor_values_clustered_by_group() {
+        let values = grouped_values("cursor");
+        let prop = USS_PROPERTIES.get("cursor").unwrap();
+        assert_eq!(values.len(), prop.values.len());
+
+        // Each group is contiguous and the groups follow the table order
+        let mut groups: Vec<&str> = Vec::new();
+        for (_, group) in &values {
+            let group = group.as_deref().unwrap_or("");
+            if groups.last() != Some(&group) {
+                assert!(!groups.contains(&group), "{} split: {:?}", group, values);
+                groups.push(group);
+            }
+        }
+        let table: Vec<&str> = prop.value_groups.iter().map(|(name, _)| *name).collect();
+        assert_eq!(groups[..table.len()], table[..]);
+    }
+
+    #[test]
+    fn ungrouped_properties_sort_by_label() {
+        let items = get_property_value_completions("-unity-font-style", "");
+        assert!(items.iter().all(|item| item.sort_text.is_none()));
+        assert!(items.iter().all(|item| item.label_details.is_none()));
+        assert_eq!(
+            grouped_values("-unity-font-style"),
+            ["bold", "bold-and-italic", "italic", "normal"].map(|label| (label.to_string(), None))
+        );
+    }
+
+    #[test]
+    fn universal_selector_offered_with_its_cost() {
+        let documented = |item: &CompletionItem| match &item.documentation {
+            Some(Documentation::MarkupContent(content)) => {
+                content.value.contains(UNIVERSAL_SELECTOR_DESCRIPTION)
+                    && content.value.contains(USS_BEST_PRACTICES_URL)
+            }
+            _ => false,
+        };
+
+        let items = completions("|");
+        let item = items.iter().find(|item| item.label == "*").unwrap();
+        assert_eq!(item.insert_text.as_deref(), Some("* {\n    $0\n}"));
+        assert!(documented(item));
+
+        let items = completions(".a, |");
+        let item = items.iter().find(|item| item.label == "*").unwrap();
+        assert_eq!(item.kind, Some(CompletionItemKind::OPERATOR));
+        assert_eq!(item.insert_text, None);
+        assert!(documented(item));
+    }
+
+    #[test]
+    fn completions_at_line_and_text_ends() {
+        let variants = |line: &str| {
+            [
+                format!(".a {{\n    {}\n}}\n", line),
+                format!(".a {{\r\n    {}\r\n}}\r\n", line),
+                format!(".a {{\n    {}", line),
+                format!(".a {{\n    {}\n", line),
+                format!(".a {{\r\n    {}\r\n", line),
+            ]
+        };
+        let labels = |text: &str| -> Vec<String> {
+            completions(text)
+                .into_iter()
+                .map(|item| item.label)
+                .collect()
+        };
+
+        for text in variants("colo|") {
+            assert_eq!(
+                context(&text),
+                CompletionContext::PropertyName,
+                "{:?}",
+                text
+            );
+            assert!(labels(&text).contains(&"color".to_string()), "{:?}", text);
+        }
+        for text in variants("color: re|") {
+            assert_eq!(context(&text), value("color"), "{:?}", text);
+            assert!(labels(&text).contains(&"red".to_string()), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn uxml_classes_offered_after_document_classes() {
+        let (doc, position) = at_cursor(".panel { }\n.|");
+        let uxml_classes = HashSet::from(["toolbar".to_string(), "panel".to_string()]);
+        let items = get_completions(
+            &doc,
+            position,
+            &Settings::default(),
+            &HashSet::new(),
+            &uxml_classes,
+        );
+
+        let details: Vec<(String, Option<String>)> = {
+            let mut items = items.clone();
+            items.sort_by_key(|item| item.sort_text.clone());
+            items
+                .into_iter()
+                .map(|item| (item.label, item.detail))
+                .collect()
+        };
+        assert_eq!(
+            details,
+            [
+                ("panel".to_string(), Some("Class selector".to_string())),
+                (
+                    "toolbar".to_string(),
+                    Some("Class used in UXML".to_string())
+                ),
+            ]
+        );
+    }
+}
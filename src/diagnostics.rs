@@ -2,11 +2,18 @@
 //!
 //! Provides syntax and semantic diagnostics for USS documents.
 
-use crate::document::Document;
-use crate::uss_data::USS_PROPERTIES;
+use crate::document::{defined_variables, Document};
+use crate::parser::{normalize_selector, parse, Declaration, Rule, SelectorErrorKind, Stylesheet};
+use crate::settings::{MissingSemicolon, Settings, TargetContext};
+use crate::tokenizer::{line_brace_deltas, tokenize, Token, TokenKind};
+use crate::uss_data::{
+    NON_TEXT_ELEMENTS, USS_BEST_PRACTICES_URL, USS_PROPERTIES, USS_PSEUDO_CLASSES, UXML_ELEMENTS,
+};
+use crate::values::{validate_separators, validate_transform_origin};
+use lsp_types::*;
 use once_cell::sync::Lazy;
 use regex::Regex;
-use tower_lsp::lsp_types::*;
+use std::collections::{HashMap, HashSet};
 
 /// Regex patterns for diagnostics
 static PROPERTY_PATTERN: Lazy<Regex> =
@@ -16,6 +23,9 @@ static PROPERTY_PATTERN: Lazy<Regex> =
 static SELECTOR_PATTERN: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^[.#\w\[\]:,\s>+~*-]+\s*\{").unwrap());
 
+/// var() usages, capturing the variable name
+static VAR_USAGE_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"var\s*\(\s*(--[\w-]+)").unwrap());
+
 static HEX_COLOR_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"#([0-9A-Fa-f]+)\b").unwrap());
 
 #[allow(dead_code)]
@@ -28,21 +38,164 @@ static UNCLOSED_PAREN_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\([^)]*$"
 static INVALID_SELECTOR_PATTERN: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^[^.#\w\[\]:,\s>+~*-]").unwrap());
 
+/// A property that has no visible effect without a companion property in the same rule
+pub struct CompanionRule {
+    /// Diagnostic code, also used to enable or disable the check
+    pub code: &'static str,
+    /// Properties that need the companion
+    pub properties: &'static [&'static str],
+    /// The companion property
+    pub companion: &'static str,
+    /// Declaration inserted by the quickfix
+    pub stub: &'static str,
+}
+
+/// Known companion property requirements
+pub static COMPANION_RULES: &[CompanionRule] = &[
+    CompanionRule {
+        code: "tint-without-background-image",
+        properties: &["-unity-background-image-tint-color"],
+        companion: "background-image",
+        stub: "background-image: url(\"\");",
+    },
+    CompanionRule {
+        code: "slice-without-background-image",
+        properties: &[
+            "-unity-slice-left",
+            "-unity-slice-right",
+            "-unity-slice-top",
+            "-unity-slice-bottom",
+            "-unity-slice-scale",
+        ],
+        companion: "background-image",
+        stub: "background-image: url(\"\");",
+    },
+];
+
 /// Get diagnostics for a USS document
-pub fn get_diagnostics(doc: &Document) -> Vec<Diagnostic> {
+///
+/// `workspace_variables` holds the variables defined in other style sheets of
+/// the workspace, which count as defined for var() usages.
+pub fn get_diagnostics(
+    doc: &Document,
+    uri: &Url,
+    settings: &Settings,
+    workspace_variables: &HashSet<String>,
+) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
     let text = doc.get_text();
+    let tokens = tokenize(&text);
 
-    // Track brace balance
+    // Track brace balance (braces in comments and strings are ignored)
+    let brace_deltas = line_brace_deltas(&text, &tokens);
     let mut brace_depth = 0;
     let mut in_declaration_block = false;
 
     for (line_num, line) in text.lines().enumerate() {
-        let line_diagnostics =
-            check_line(line, line_num, &mut brace_depth, &mut in_declaration_block);
+        let mut line_diagnostics = check_line(
+            line,
+            line_num,
+            brace_deltas[line_num],
+            &mut brace_depth,
+            &mut in_declaration_block,
+        );
+        // Line checks report byte columns; convert them to the wire encoding
+        for diagnostic in &mut line_diagnostics {
+            for position in [&mut diagnostic.range.start, &mut diagnostic.range.end] {
+                position.character = doc.codec.column(line, position.character as usize);
+            }
+        }
         diagnostics.extend(line_diagnostics);
     }
 
+    let stylesheet = parse(&text);
+    diagnostics.extend(check_companion_properties(doc, &stylesheet, settings));
+
+    if settings.diagnostics.is_enabled("missing-semicolon", true) {
+        diagnostics.extend(check_missing_semicolons(
+            doc,
+            &stylesheet,
+            settings.diagnostics.missing_semicolon,
+        ));
+    }
+
+    if settings.diagnostics.is_enabled("wrong-separator", true) {
+        diagnostics.extend(check_value_separators(doc, &stylesheet));
+    }
+
+    if settings.diagnostics.is_enabled("unavailable-element", true) {
+        diagnostics.extend(check_element_selectors(
+            doc,
+            &stylesheet,
+            settings.target_context,
+        ));
+    }
+
+    if settings
+        .diagnostics
+        .is_enabled("invalid-transform-origin", true)
+    {
+        diagnostics.extend(check_transform_origins(doc, &stylesheet));
+    }
+
+    if settings
+        .diagnostics
+        .is_enabled("universal-key-selector", false)
+    {
+        diagnostics.extend(check_universal_key_selectors(doc, &stylesheet));
+    }
+
+    diagnostics.extend(
+        check_selector_separators(doc, &stylesheet)
+            .into_iter()
+            .filter(|d| match &d.code {
+                Some(NumberOrString::String(code)) => settings.diagnostics.is_enabled(code, true),
+                _ => true,
+            }),
+    );
+
+    if settings
+        .diagnostics
+        .is_enabled("shadowed-declaration", true)
+    {
+        diagnostics.extend(check_shadowed_declarations(doc, uri, &stylesheet));
+    }
+
+    if settings
+        .diagnostics
+        .is_enabled("text-property-on-non-text-element", false)
+    {
+        diagnostics.extend(check_text_properties(doc, &stylesheet));
+    }
+
+    if settings
+        .diagnostics
+        .is_enabled("identical-rule-body", false)
+    {
+        diagnostics.extend(check_identical_rule_bodies(doc, uri, &stylesheet));
+    }
+
+    if settings
+        .diagnostics
+        .is_enabled("variable-used-before-definition", true)
+    {
+        diagnostics.extend(check_variable_order(
+            doc,
+            uri,
+            &stylesheet,
+            workspace_variables,
+        ));
+    }
+
+    if settings.diagnostics.is_enabled("undefined-variable", true) {
+        diagnostics.extend(check_undefined_variables(
+            doc,
+            &text,
+            &tokens,
+            workspace_variables,
+        ));
+    }
+
     // Check for unclosed braces at end of document
     if brace_depth > 0 {
         diagnostics.push(Diagnostic {
@@ -86,33 +239,79 @@ pub fn get_diagnostics(doc: &Document) -> Vec<Diagnostic> {
         });
     }
 
+    remove_ignored(&mut diagnostics, &text, &tokens);
     diagnostics
 }
 
+/// Remove diagnostics suppressed by ignore comments
+///
+/// `/* uss-ignore */` suppresses diagnostics on its own line and
+/// `/* uss-ignore-next-line */` on the following line. Either may list the
+/// codes to suppress (`/* uss-ignore-next-line universal-key-selector */`);
+/// without codes, every diagnostic on the line is suppressed.
+fn remove_ignored(diagnostics: &mut Vec<Diagnostic>, text: &str, tokens: &[Token]) {
+    // (line, codes) pairs; empty codes suppress everything
+    let mut ignores: Vec<(u32, Vec<&str>)> = Vec::new();
+
+    for token in tokens.iter().filter(|t| t.is_comment()) {
+        let body = token
+            .text(text)
+            .trim_start_matches("/*")
+            .trim_end_matches("*/")
+            .trim();
+        let (directive, codes) = body.split_once(char::is_whitespace).unwrap_or((body, ""));
+        let line = text[..token.start].matches('\n').count() as u32;
+        let line = match directive {
+            "uss-ignore" => line,
+            "uss-ignore-next-line" => line + 1,
+            _ => continue,
+        };
+        let codes = codes
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|c| !c.is_empty())
+            .collect();
+        ignores.push((line, codes));
+    }
+    if ignores.is_empty() {
+        return;
+    }
+
+    diagnostics.retain(|diagnostic| {
+        let code = match &diagnostic.code {
+            Some(NumberOrString::String(code)) => Some(code.as_str()),
+            _ => None,
+        };
+        !ignores.iter().any(|(line, codes)| {
+            *line == diagnostic.range.start.line
+                && (codes.is_empty() || code.is_some_and(|code| codes.contains(&code)))
+        })
+    });
+}
+
 /// Check a single line for diagnostics
 fn check_line(
     line: &str,
     line_num: usize,
+    brace_delta: i32,
     brace_depth: &mut i32,
     in_declaration_block: &mut bool,
 ) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
     let trimmed = line.trim();
+    let depth_at_start = *brace_depth;
+
+    // Track brace depth
+    *brace_depth += brace_delta;
+    *in_declaration_block = *brace_depth > 0;
 
     // Skip empty lines and comments
     if trimmed.is_empty() || trimmed.starts_with("/*") || trimmed.starts_with("//") {
         return diagnostics;
     }
 
-    // Track brace depth
-    let open_braces = line.matches('{').count() as i32;
-    let close_braces = line.matches('}').count() as i32;
-
-    *brace_depth += open_braces - close_braces;
-    *in_declaration_block = *brace_depth > 0;
-
-    // Check for property declarations inside blocks
-    if *in_declaration_block {
+    // Check for property declarations inside blocks; a line opening a block is
+    // a selector even when it looks like one (`Button: hover {`)
+    if *in_declaration_block && depth_at_start > 0 {
         diagnostics.extend(check_property_declaration(line, line_num));
     }
 
@@ -122,30 +321,194 @@ fn check_line(
     // Check for unclosed parentheses in functions
     diagnostics.extend(check_unclosed_parens(line, line_num));
 
-    // Check for missing semicolons in declarations
-    if *in_declaration_block
-        && !trimmed.is_empty()
-        && trimmed.contains(':')
-        && !trimmed.ends_with(';')
-        && !trimmed.ends_with('{')
-        && !trimmed.ends_with('}')
-    {
-        // Allow multi-line values, but warn about potential missing semicolons
-        // Only warn if this looks like a complete declaration
-        let colon_count = trimmed.matches(':').count();
-        if colon_count == 1 && !trimmed.contains("url(") && !trimmed.contains("var(") {
+    diagnostics
+}
+
+/// Check for var() usages of variables not defined anywhere in the workspace
+///
+/// Variables provided by Unity themes (`--unity-*`) are assumed to exist.
+fn check_undefined_variables(
+    doc: &Document,
+    text: &str,
+    tokens: &[Token],
+    workspace_variables: &HashSet<String>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let local_variables = defined_variables(text);
+
+    for caps in VAR_USAGE_PATTERN.captures_iter(text) {
+        let Some(name) = caps.get(1) else {
+            continue;
+        };
+        let in_comment = tokens
+            .iter()
+            .any(|t| t.is_comment() && t.start <= name.start() && name.end() <= t.end);
+        if in_comment
+            || name.as_str().starts_with("--unity-")
+            || local_variables.contains(name.as_str())
+            || workspace_variables.contains(name.as_str())
+        {
+            continue;
+        }
+
+        diagnostics.push(Diagnostic {
+            range: Range {
+                start: doc.byte_offset_to_position(name.start()),
+                end: doc.byte_offset_to_position(name.end()),
+            },
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String("undefined-variable".to_string())),
+            source: Some("uss".to_string()),
+            message: format!("Undefined variable '{}'", name.as_str()),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+/// Check for variables used in a rule before their only definition in that rule
+///
+/// `color: var(--x); --x: red;` depends on declaration order within the rule,
+/// which differs between Unity versions. Usages in other rules are not checked,
+/// nor variables also defined in other style sheets of the workspace.
+fn check_variable_order(
+    doc: &Document,
+    uri: &Url,
+    stylesheet: &Stylesheet,
+    workspace_variables: &HashSet<String>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut definitions: HashMap<&str, usize> = HashMap::new();
+    for decl in stylesheet.rules.iter().flat_map(|r| &r.declarations) {
+        *definitions.entry(decl.property.as_str()).or_default() += 1;
+    }
+
+    for rule in &stylesheet.rules {
+        for (index, decl) in rule.declarations.iter().enumerate() {
+            for caps in VAR_USAGE_PATTERN.captures_iter(&decl.value) {
+                let Some(name) = caps.get(1) else {
+                    continue;
+                };
+                if definitions.get(name.as_str()) != Some(&1)
+                    || workspace_variables.contains(name.as_str())
+                {
+                    continue;
+                }
+                let Some(definition) = rule.declarations[index + 1..]
+                    .iter()
+                    .find(|d| d.property == name.as_str())
+                else {
+                    continue;
+                };
+
+                diagnostics.push(Diagnostic {
+                    range: Range {
+                        start: doc.byte_offset_to_position(decl.value_start + name.start()),
+                        end: doc.byte_offset_to_position(decl.value_start + name.end()),
+                    },
+                    severity: Some(DiagnosticSeverity::INFORMATION),
+                    code: Some(NumberOrString::String(
+                        "variable-used-before-definition".to_string(),
+                    )),
+                    source: Some("uss".to_string()),
+                    message: format!(
+                        "'{}' is used before its definition later in this rule; define it in :root or above this declaration",
+                        name.as_str()
+                    ),
+                    related_information: Some(vec![related_in_document(
+                        doc,
+                        uri,
+                        definition.property_start,
+                        definition.end,
+                        format!("'{}' is defined here", name.as_str()),
+                    )]),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Check for properties set without their companion property in the same rule
+fn check_companion_properties(
+    doc: &Document,
+    stylesheet: &Stylesheet,
+    settings: &Settings,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for companion_rule in COMPANION_RULES {
+        if !settings.diagnostics.is_enabled(companion_rule.code, true) {
+            continue;
+        }
+
+        for rule in &stylesheet.rules {
+            if rule.declaration(companion_rule.companion).is_some() {
+                continue;
+            }
+            // Only the first offending declaration is reported per rule
+            let Some(decl) = rule
+                .declarations
+                .iter()
+                .find(|d| companion_rule.properties.contains(&d.property.as_str()))
+            else {
+                continue;
+            };
+
             diagnostics.push(Diagnostic {
                 range: Range {
-                    start: Position {
-                        line: line_num as u32,
-                        character: line.len().saturating_sub(1) as u32,
-                    },
-                    end: Position {
-                        line: line_num as u32,
-                        character: line.len() as u32,
-                    },
+                    start: doc.byte_offset_to_position(decl.property_start),
+                    end: doc.byte_offset_to_position(decl.property_end),
+                },
+                severity: Some(DiagnosticSeverity::HINT),
+                code: Some(NumberOrString::String(companion_rule.code.to_string())),
+                source: Some("uss".to_string()),
+                message: format!(
+                    "'{}' has no effect without '{}' in the same rule",
+                    decl.property, companion_rule.companion
+                ),
+                ..Default::default()
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// Check for declarations without a trailing semicolon
+///
+/// In `except-last` mode the final declaration of a block may omit it.
+fn check_missing_semicolons(
+    doc: &Document,
+    stylesheet: &Stylesheet,
+    mode: MissingSemicolon,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    if mode == MissingSemicolon::Off {
+        return diagnostics;
+    }
+
+    for rule in &stylesheet.rules {
+        let count = rule.declarations.len();
+        for (i, decl) in rule.declarations.iter().enumerate() {
+            if decl.has_semicolon || decl.value.is_empty() {
+                continue;
+            }
+            if mode == MissingSemicolon::ExceptLast && i + 1 == count {
+                continue;
+            }
+
+            let last_char_len = decl.value.chars().last().map_or(0, char::len_utf8);
+            diagnostics.push(Diagnostic {
+                range: Range {
+                    start: doc.byte_offset_to_position(decl.value_end - last_char_len),
+                    end: doc.byte_offset_to_position(decl.value_end),
                 },
                 severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("missing-semicolon".to_string())),
                 source: Some("uss".to_string()),
                 message: "Missing semicolon at end of declaration".to_string(),
                 ..Default::default()
@@ -156,6 +519,462 @@ fn check_line(
     diagnostics
 }
 
+/// Check for the universal selector as the key selector of a complex selector
+///
+/// `.panel *` makes UI Toolkit test every descendant of `.panel`. Opt-in.
+fn check_universal_key_selectors(doc: &Document, stylesheet: &Stylesheet) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for rule in &stylesheet.rules {
+        let tokens = tokenize(&rule.selector);
+        for selector in tokens.split(|t| t.kind == TokenKind::Delim(',')) {
+            let (Some(first), Some(last)) = (
+                selector
+                    .iter()
+                    .position(|t| t.kind != TokenKind::Whitespace),
+                selector
+                    .iter()
+                    .rposition(|t| t.kind != TokenKind::Whitespace),
+            ) else {
+                continue;
+            };
+            let selector = &selector[first..=last];
+            if selector.iter().any(|t| t.kind == TokenKind::Comment) {
+                continue;
+            }
+
+            // The key compound follows the last combinator
+            let Some(combinator) = selector
+                .iter()
+                .rposition(|t| matches!(t.kind, TokenKind::Whitespace | TokenKind::Delim('>')))
+            else {
+                continue;
+            };
+            let Some(key) = selector
+                .get(combinator + 1)
+                .filter(|t| t.kind == TokenKind::Delim('*'))
+            else {
+                continue;
+            };
+            let text = &rule.selector[selector[0].start..selector[selector.len() - 1].end];
+
+            diagnostics.push(Diagnostic {
+                range: Range {
+                    start: doc.byte_offset_to_position(rule.selector_start + key.start),
+                    end: doc.byte_offset_to_position(rule.selector_start + key.end),
+                },
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("universal-key-selector".to_string())),
+                code_description: Url::parse(USS_BEST_PRACTICES_URL)
+                    .ok()
+                    .map(|href| CodeDescription { href }),
+                source: Some("uss".to_string()),
+                message: format!(
+                    "'{}' ends in the universal selector, which matches every descendant and is slow to resolve",
+                    text
+                ),
+                ..Default::default()
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// Check selectors for malformed pseudo-class separators
+///
+/// `Button: hover`, `Button::hover`, and `Button;hover` all break the rule.
+/// Every diagnostic range is fixed by replacing it with `:`.
+fn check_selector_separators(doc: &Document, stylesheet: &Stylesheet) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for error in stylesheet.rules.iter().flat_map(|r| &r.selector_errors) {
+        let (code, message) = match error.kind {
+            SelectorErrorKind::SpaceAfterColon => (
+                "pseudo-class-space",
+                format!(
+                    "Unexpected space after ':'; write ':{}' to select the pseudo-class",
+                    error.name
+                ),
+            ),
+            SelectorErrorKind::DoubleColon => (
+                "pseudo-class-double-colon",
+                format!(
+                    "USS has no pseudo-elements; write ':{}' with a single colon",
+                    error.name
+                ),
+            ),
+            SelectorErrorKind::Semicolon => {
+                if !USS_PSEUDO_CLASSES.iter().any(|p| p.name == error.name) {
+                    continue;
+                }
+                (
+                    "pseudo-class-semicolon",
+                    format!(
+                        "';' is not allowed in a selector; write ':{}' to select the pseudo-class",
+                        error.name
+                    ),
+                )
+            }
+        };
+
+        diagnostics.push(Diagnostic {
+            range: Range {
+                start: doc.byte_offset_to_position(error.start),
+                end: doc.byte_offset_to_position(error.end),
+            },
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(code.to_string())),
+            source: Some("uss".to_string()),
+            message,
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+/// Check for declarations overridden by a later rule with the same selector
+///
+/// `.toolbar Button { color: red; }` followed by `.toolbar Button { color: blue; }`
+/// never shows red. In a selector list every selector must be overridden.
+/// Variables are exempt, and pseudo-classes make selectors differ.
+fn check_shadowed_declarations(
+    doc: &Document,
+    uri: &Url,
+    stylesheet: &Stylesheet,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let selectors: Vec<Vec<String>> = stylesheet
+        .rules
+        .iter()
+        .map(|rule| {
+            rule.selectors()
+                .into_iter()
+                .map(normalize_selector)
+                .collect()
+        })
+        .collect();
+
+    for (index, rule) in stylesheet.rules.iter().enumerate() {
+        for decl in &rule.declarations {
+            if decl.property.starts_with("--") {
+                continue;
+            }
+
+            // The last later declaration of the property for each selector wins
+            let overriding: Option<Vec<&Declaration>> = selectors[index]
+                .iter()
+                .map(|selector| {
+                    stylesheet.rules[index + 1..]
+                        .iter()
+                        .zip(&selectors[index + 1..])
+                        .filter(|(_, later)| later.contains(selector))
+                        .filter_map(|(later, _)| {
+                            later
+                                .declarations
+                                .iter()
+                                .rfind(|d| d.property == decl.property)
+                        })
+                        .next_back()
+                })
+                .collect();
+            let Some(mut overriding) = overriding else {
+                continue;
+            };
+            overriding.dedup_by_key(|d| d.property_start);
+
+            diagnostics.push(Diagnostic {
+                range: Range {
+                    start: doc.byte_offset_to_position(decl.property_start),
+                    end: doc.byte_offset_to_position(decl.end),
+                },
+                severity: Some(DiagnosticSeverity::HINT),
+                code: Some(NumberOrString::String("shadowed-declaration".to_string())),
+                source: Some("uss".to_string()),
+                message: format!(
+                    "'{}' is always overridden by a later rule with the same selector",
+                    decl.property
+                ),
+                related_information: Some(
+                    overriding
+                        .iter()
+                        .map(|d| {
+                            related_in_document(
+                                doc,
+                                uri,
+                                d.property_start,
+                                d.end,
+                                format!("Overridden here by '{}: {}'", d.property, d.value),
+                            )
+                        })
+                        .collect(),
+                ),
+                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                ..Default::default()
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// Check for rules with the same declarations as an earlier rule
+///
+/// `.a { color: red; }` and `.b { color: red; }` could be a single `.a, .b`
+/// rule. Every rule after the first of a group is reported. Opt-in.
+fn check_identical_rule_bodies(
+    doc: &Document,
+    uri: &Url,
+    stylesheet: &Stylesheet,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let rules: Vec<(&Rule, Vec<(&str, String)>)> = stylesheet
+        .rules
+        .iter()
+        .filter(|rule| !rule.declarations.is_empty())
+        .map(|rule| (rule, rule.normalized_body()))
+        .collect();
+
+    // Grouped by the bodies themselves, so only equal bodies share a group
+    let mut groups: HashMap<&[(&str, String)], Vec<&Rule>> = HashMap::new();
+    for (rule, body) in &rules {
+        groups.entry(body.as_slice()).or_default().push(rule);
+    }
+
+    for (rule, body) in &rules {
+        let group = &groups[body.as_slice()];
+        if std::ptr::eq(group[0], *rule) {
+            continue;
+        }
+
+        diagnostics.push(Diagnostic {
+            range: Range {
+                start: doc.byte_offset_to_position(rule.selector_start),
+                end: doc.byte_offset_to_position(rule.selector_end),
+            },
+            severity: Some(DiagnosticSeverity::HINT),
+            code: Some(NumberOrString::String("identical-rule-body".to_string())),
+            source: Some("uss".to_string()),
+            message: format!(
+                "Same declarations as '{}'; the rules can be merged into one",
+                group[0].selector
+            ),
+            related_information: Some(
+                group
+                    .iter()
+                    .filter(|other| !std::ptr::eq(**other, *rule))
+                    .map(|other| {
+                        related_in_document(
+                            doc,
+                            uri,
+                            other.selector_start,
+                            other.selector_end,
+                            format!("Same declarations in '{}'", other.selector),
+                        )
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+/// Related information pointing into the checked document
+fn related_in_document(
+    doc: &Document,
+    uri: &Url,
+    start: usize,
+    end: usize,
+    message: String,
+) -> DiagnosticRelatedInformation {
+    DiagnosticRelatedInformation {
+        location: Location {
+            uri: uri.clone(),
+            range: Range {
+                start: doc.byte_offset_to_position(start),
+                end: doc.byte_offset_to_position(end),
+            },
+        },
+        message,
+    }
+}
+
+/// Check for text-only properties in rules that only target non-text elements
+///
+/// `VisualElement { -unity-text-align: middle-center; }` does not align the
+/// element's children. Rules with any selector whose key is not a known
+/// non-text element (such as class-only selectors) are exempt. Opt-in.
+fn check_text_properties(doc: &Document, stylesheet: &Stylesheet) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for rule in &stylesheet.rules {
+        let elements: Option<Vec<&str>> = rule
+            .selectors()
+            .into_iter()
+            .map(key_type_selector)
+            .map(|element| element.filter(|name| NON_TEXT_ELEMENTS.contains(name)))
+            .collect();
+        let Some(element) = elements.and_then(|elements| elements.first().copied()) else {
+            continue;
+        };
+
+        for decl in &rule.declarations {
+            let Some(prop) = USS_PROPERTIES.get(decl.property.as_str()) else {
+                continue;
+            };
+            if !prop.applies_to_text_only {
+                continue;
+            }
+
+            let effect = if prop.inherited {
+                "it only takes effect on descendant text elements that inherit it"
+            } else {
+                "it has no effect here"
+            };
+            diagnostics.push(Diagnostic {
+                range: Range {
+                    start: doc.byte_offset_to_position(decl.property_start),
+                    end: doc.byte_offset_to_position(decl.property_end),
+                },
+                severity: Some(DiagnosticSeverity::HINT),
+                code: Some(NumberOrString::String(
+                    "text-property-on-non-text-element".to_string(),
+                )),
+                source: Some("uss".to_string()),
+                message: format!(
+                    "'{}' affects the element's own text, but '{}' displays no text; {}",
+                    prop.name, element, effect
+                ),
+                ..Default::default()
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// Type selector of the key compound of a selector (`Button` in `.bar > Button:hover`)
+fn key_type_selector(selector: &str) -> Option<&str> {
+    let key = selector
+        .split(|c: char| c.is_whitespace() || c == '>')
+        .rfind(|part| !part.is_empty())?;
+    let end = key.find(['.', '#', ':', '[']).unwrap_or(key.len());
+    let name = &key[..end];
+    (!name.is_empty() && name != "*").then_some(name)
+}
+
+/// Check for commas in space-separated values and spaces in comma-separated lists
+fn check_value_separators(doc: &Document, stylesheet: &Stylesheet) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for decl in stylesheet.rules.iter().flat_map(|r| &r.declarations) {
+        for error in validate_separators(&decl.property, &decl.value) {
+            diagnostics.push(Diagnostic {
+                range: Range {
+                    start: doc.byte_offset_to_position(decl.value_start + error.start),
+                    end: doc.byte_offset_to_position(decl.value_start + error.end),
+                },
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String("wrong-separator".to_string())),
+                source: Some("uss".to_string()),
+                message: format!(
+                    "'{}' values are separated by {}, not {}",
+                    decl.property,
+                    error.expected.plural(),
+                    error.found.plural()
+                ),
+                ..Default::default()
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// Check `transform-origin` values against their two-axis grammar
+fn check_transform_origins(doc: &Document, stylesheet: &Stylesheet) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for decl in stylesheet
+        .rules
+        .iter()
+        .flat_map(|r| &r.declarations)
+        .filter(|d| d.property == "transform-origin")
+    {
+        for error in validate_transform_origin(&decl.value) {
+            diagnostics.push(Diagnostic {
+                range: Range {
+                    start: doc.byte_offset_to_position(decl.value_start + error.start),
+                    end: doc.byte_offset_to_position(decl.value_start + error.end),
+                },
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String(
+                    "invalid-transform-origin".to_string(),
+                )),
+                source: Some("uss".to_string()),
+                message: error.message,
+                ..Default::default()
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// Check type selectors against the target UI context
+///
+/// Walks the selector tokens of each rule, so comments, class and
+/// pseudo-class names, and declaration values are never mistaken for
+/// element names.
+fn check_element_selectors(
+    doc: &Document,
+    stylesheet: &Stylesheet,
+    target: TargetContext,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for rule in &stylesheet.rules {
+        let tokens = tokenize(&rule.selector);
+        for (i, token) in tokens.iter().enumerate() {
+            let previous = i.checked_sub(1).map(|p| tokens[p].kind);
+            if token.kind != TokenKind::Ident
+                || matches!(previous, Some(TokenKind::Delim('.' | ':')))
+            {
+                continue;
+            }
+            let name = token.text(&rule.selector);
+            let Some(elem) = UXML_ELEMENTS.iter().find(|e| e.name == name) else {
+                continue;
+            };
+            if target.allows_element(elem) {
+                continue;
+            }
+
+            diagnostics.push(Diagnostic {
+                range: Range {
+                    start: doc.byte_offset_to_position(rule.selector_start + token.start),
+                    end: doc.byte_offset_to_position(rule.selector_start + token.end),
+                },
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("unavailable-element".to_string())),
+                source: Some("uss".to_string()),
+                message: format!(
+                    "'{}' is an editor-only element ({}) and is not available in {}",
+                    elem.name,
+                    elem.namespace,
+                    target.description()
+                ),
+                ..Default::default()
+            });
+        }
+    }
+
+    diagnostics
+}
+
 /// Check property declarations for validity
 fn check_property_declaration(line: &str, line_num: usize) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
@@ -320,3 +1139,474 @@ fn check_unclosed_parens(line: &str, line_num: usize) -> Vec<Diagnostic> {
 
     diagnostics
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URI: &str = "file:///theme.uss";
+
+    fn diagnostics_with(text: &str, settings: &Settings) -> Vec<Diagnostic> {
+        let doc = Document::new(text.to_string(), 1);
+        get_diagnostics(&doc, &URI.parse().unwrap(), settings, &HashSet::new())
+    }
+
+    /// Diagnostics with variables defined in other style sheets
+    fn diagnostics_in_workspace(text: &str, workspace_variables: &[&str]) -> Vec<Diagnostic> {
+        let doc = Document::new(text.to_string(), 1);
+        let workspace_variables = workspace_variables.iter().map(|v| v.to_string()).collect();
+        get_diagnostics(
+            &doc,
+            &URI.parse().unwrap(),
+            &Settings::default(),
+            &workspace_variables,
+        )
+    }
+
+    fn diagnostics(text: &str) -> Vec<Diagnostic> {
+        diagnostics_with(text, &Settings::default())
+    }
+
+    /// Ranges (as `line:start-end`) of the diagnostics with a code
+    fn ranges(diagnostics: &[Diagnostic], code: &str) -> Vec<String> {
+        diagnostics
+            .iter()
+            .filter(|d| d.code == Some(NumberOrString::String(code.to_string())))
+            .map(|d| {
+                format!(
+                    "{}:{}-{}",
+                    d.range.start.line, d.range.start.character, d.range.end.character
+                )
+            })
+            .collect()
+    }
+
+    /// Lines of the diagnostics whose message starts with a prefix
+    fn lines(diagnostics: &[Diagnostic], message: &str) -> Vec<u32> {
+        diagnostics
+            .iter()
+            .filter(|d| d.message.starts_with(message))
+            .map(|d| d.range.start.line)
+            .collect()
+    }
+
+    fn brace_errors(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics
+            .iter()
+            .map(|d| d.message.as_str())
+            .filter(|m| m.starts_with("Unclosed brace") || m.starts_with("Extra closing brace"))
+            .collect()
+    }
+
+    #[test]
+    fn braces_in_strings_do_not_change_depth() {
+        let diagnostics = diagnostics(
+            ".a {\n    background-image: url(\"icons/a{.png\");\n    colr: red;\n}\n\
+             .b {\n    -unity-font-definition: url(\"b}}.ttf\");\n    colr: red;\n}\n",
+        );
+        assert_eq!(lines(&diagnostics, "Unknown USS property"), [2, 6]);
+        assert!(brace_errors(&diagnostics).is_empty());
+    }
+
+    #[test]
+    fn braces_in_comments_do_not_change_depth() {
+        let diagnostics =
+            diagnostics(".a { /* } */\n    colr: red;\n}\n/* { */\n.b {\n    colr: red;\n}\n");
+        assert_eq!(lines(&diagnostics, "Unknown USS property"), [1, 5]);
+        assert!(brace_errors(&diagnostics).is_empty());
+    }
+
+    #[test]
+    fn multiple_rules_on_one_line() {
+        let diagnostics = diagnostics(
+            ".a { color: red; } .b { color: blue; }\n\
+             .c:hover {}\n\
+             .d {} .e {\n    colr: red;\n}\n",
+        );
+        assert_eq!(lines(&diagnostics, "Unknown USS property"), [3]);
+        assert!(brace_errors(&diagnostics).is_empty());
+
+        let diagnostics = diagnostics_with(".a { } .b {\n", &Settings::default());
+        assert_eq!(
+            brace_errors(&diagnostics),
+            ["Unclosed brace(s): 1 opening brace(s) without closing"]
+        );
+    }
+
+    #[test]
+    fn wrong_separators_point_at_the_separator() {
+        let diagnostics = diagnostics(
+            ".a {\n    margin: 10px, 5px;\n    transition-property: opacity translate;\n}\n",
+        );
+        assert_eq!(
+            ranges(&diagnostics, "wrong-separator"),
+            ["1:16-17", "2:32-33"]
+        );
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|d| d.code == Some(NumberOrString::String("wrong-separator".to_string())))
+                .map(|d| d.message.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "'margin' values are separated by spaces, not commas",
+                "'transition-property' values are separated by commas, not spaces",
+            ]
+        );
+    }
+
+    #[test]
+    fn wrong_separators_in_transition_shorthand() {
+        let diagnostics = diagnostics(
+            ".a {\n    transition: opacity 0.3s ease, scale 1s;\n}\n\
+             .b {\n    transition: opacity scale 1s;\n}\n\
+             .c {\n    transition: opacity, 1s;\n}\n",
+        );
+        assert_eq!(
+            ranges(&diagnostics, "wrong-separator"),
+            ["4:23-24", "7:23-24"]
+        );
+    }
+
+    const SEMICOLONS: &str = ".a { width: 100px }\n\
+                              .b {\n    width: 100px;\n    height: 50px\n}\n\
+                              .c { height: 50px /* ; } */ }\n\
+                              .d {\n    width: 100px /* last; */\n    height: 50px;\n}\n";
+
+    fn missing_semicolons(mode: MissingSemicolon) -> Vec<String> {
+        let mut settings = Settings::default();
+        settings.diagnostics.missing_semicolon = mode;
+        ranges(
+            &diagnostics_with(SEMICOLONS, &settings),
+            "missing-semicolon",
+        )
+    }
+
+    #[test]
+    fn missing_semicolons_always_reported() {
+        assert_eq!(
+            missing_semicolons(MissingSemicolon::Always),
+            ["0:16-17", "3:15-16", "5:16-17", "7:15-16"]
+        );
+    }
+
+    #[test]
+    fn missing_semicolon_allowed_on_last_declaration() {
+        assert_eq!(
+            missing_semicolons(MissingSemicolon::ExceptLast),
+            ["7:15-16"]
+        );
+    }
+
+    #[test]
+    fn missing_semicolons_off() {
+        assert!(missing_semicolons(MissingSemicolon::Off).is_empty());
+    }
+
+    #[test]
+    fn space_after_pseudo_class_colon() {
+        let diagnostics = diagnostics("Button: hover {\n    color: red;\n}\n");
+        assert_eq!(ranges(&diagnostics, "pseudo-class-space"), ["0:6-8"]);
+        assert!(lines(&diagnostics, "Unknown USS property").is_empty());
+    }
+
+    #[test]
+    fn double_colon_before_pseudo_class() {
+        let diagnostics = diagnostics("Button::hover {\n    color: red;\n}\n");
+        assert_eq!(ranges(&diagnostics, "pseudo-class-double-colon"), ["0:6-8"]);
+        assert!(lines(&diagnostics, "Unknown USS property").is_empty());
+    }
+
+    #[test]
+    fn semicolon_before_pseudo_class() {
+        let reported = diagnostics("Button;hover {\n    color: red;\n}\n");
+        assert_eq!(ranges(&reported, "pseudo-class-semicolon"), ["0:6-7"]);
+        assert!(lines(&reported, "Unknown USS property").is_empty());
+
+        let unknown = diagnostics(".a { color: red;hover: x; }\n.b;c {}\n");
+        assert!(ranges(&unknown, "pseudo-class-semicolon").is_empty());
+    }
+
+    #[test]
+    fn valid_pseudo_classes_not_reported() {
+        let diagnostics = diagnostics("Button:hover, .a :focus, #b:active:checked {}\n");
+        for code in [
+            "pseudo-class-space",
+            "pseudo-class-double-colon",
+            "pseudo-class-semicolon",
+        ] {
+            assert!(ranges(&diagnostics, code).is_empty(), "{}", code);
+        }
+    }
+
+    /// Settings enabling an opt-in check
+    fn enabling(code: &str) -> Settings {
+        let mut settings = Settings::default();
+        settings.diagnostics.rules.insert(code.to_string(), true);
+        settings
+    }
+
+    #[test]
+    fn companion_properties_hinted() {
+        for rule in COMPANION_RULES {
+            for property in rule.properties {
+                let text = format!(".a {{\n    {property}: 1;\n    margin: 0;\n}}\n");
+                let diagnostics = diagnostics(&text);
+                let end = 4 + property.len();
+                assert_eq!(
+                    ranges(&diagnostics, rule.code),
+                    [format!("1:4-{end}")],
+                    "{property}"
+                );
+                assert_eq!(
+                    diagnostics[0].message,
+                    format!(
+                        "'{property}' has no effect without '{}' in the same rule",
+                        rule.companion
+                    )
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn companion_properties_satisfied() {
+        for rule in COMPANION_RULES {
+            for property in rule.properties {
+                let text = format!(
+                    ".a {{\n    {property}: 1;\n}}\n.b {{\n    {property}: 1;\n    {}: none;\n}}\n",
+                    rule.companion
+                );
+                let mut settings = Settings::default();
+                settings.diagnostics.rules.insert(rule.code.into(), false);
+                assert!(ranges(&diagnostics_with(&text, &settings), rule.code).is_empty());
+
+                // Only the rule without the companion is hinted
+                assert_eq!(ranges(&diagnostics(&text), rule.code).len(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn universal_key_selectors_warned() {
+        let text = ".panel *, .panel > *.item { }\n\
+                    * { }\n\
+                    *.item, .panel /* * */ { }\n\
+                    .ü\u{3000} *, .b\u{a0}> * { }\n";
+        let diagnostics = diagnostics_with(text, &enabling("universal-key-selector"));
+        assert_eq!(
+            ranges(&diagnostics, "universal-key-selector"),
+            ["0:7-8", "0:19-20", "3:4-5", "3:12-13"]
+        );
+        assert_eq!(
+            diagnostics[2].message,
+            "'.ü\u{3000} *' ends in the universal selector, which matches every descendant and is slow to resolve"
+        );
+    }
+
+    #[test]
+    fn universal_key_selectors_opt_in() {
+        assert!(ranges(&diagnostics(".panel * { }"), "universal-key-selector").is_empty());
+    }
+
+    const TEXT_PROPERTIES: &str = "VisualElement {\n    font-size: 12px;\n    text-overflow: ellipsis;\n    margin: 0;\n}\n\
+                                   .bar > Image:hover, ScrollView { -unity-text-align: middle-center; }\n\
+                                   Label { font-size: 12px; }\n\
+                                   VisualElement Label { text-overflow: ellipsis; }\n\
+                                   VisualElement, .title { letter-spacing: 1px; }\n";
+
+    #[test]
+    fn text_properties_on_non_text_elements_hinted() {
+        let diagnostics = diagnostics_with(
+            TEXT_PROPERTIES,
+            &enabling("text-property-on-non-text-element"),
+        );
+        assert_eq!(
+            ranges(&diagnostics, "text-property-on-non-text-element"),
+            ["1:4-13", "2:4-17", "5:33-50"]
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "'font-size' affects the element's own text, but 'VisualElement' displays no text; \
+             it only takes effect on descendant text elements that inherit it"
+        );
+        assert_eq!(
+            diagnostics[1].message,
+            "'text-overflow' affects the element's own text, but 'VisualElement' displays no text; \
+             it has no effect here"
+        );
+        assert_eq!(diagnostics[2].severity, Some(DiagnosticSeverity::HINT));
+    }
+
+    #[test]
+    fn text_properties_opt_in() {
+        assert!(ranges(
+            &diagnostics(TEXT_PROPERTIES),
+            "text-property-on-non-text-element"
+        )
+        .is_empty());
+    }
+
+    const VARIABLE_ORDER: &str = ".a {\n    color: var(--accent);\n    --accent: red;\n}\n\
+                                  .b { border-color: var(--accent); }\n\
+                                  .c { --edge: 1px; border-width: var(--edge); }\n";
+
+    #[test]
+    fn variables_used_before_definition_reported() {
+        let diagnostics = diagnostics(VARIABLE_ORDER);
+        assert_eq!(
+            ranges(&diagnostics, "variable-used-before-definition"),
+            ["1:15-23"]
+        );
+
+        let info = &diagnostics[0];
+        assert_eq!(info.severity, Some(DiagnosticSeverity::INFORMATION));
+        let related = info.related_information.as_ref().unwrap();
+        assert_eq!(related[0].location.range.start, Position::new(2, 4));
+    }
+
+    #[test]
+    fn variables_defined_in_the_workspace_not_ordered() {
+        // Another style sheet's definition may be the one that applies
+        let diagnostics = diagnostics_in_workspace(VARIABLE_ORDER, &["--accent"]);
+        assert!(ranges(&diagnostics, "variable-used-before-definition").is_empty());
+
+        let diagnostics = diagnostics_in_workspace(VARIABLE_ORDER, &["--other"]);
+        assert_eq!(
+            ranges(&diagnostics, "variable-used-before-definition"),
+            ["1:15-23"]
+        );
+    }
+
+    #[test]
+    fn shadowed_declarations_hinted() {
+        let text = ".a {\n    color: red;\n    margin: 0;\n}\n\
+                    .b, .a { color: blue; }\n\
+                    .a:hover { margin: 4px; }\n\
+                    .a.b { margin: 2px; }\n\
+                    .b { color: green; }\n";
+        let diagnostics = diagnostics(text);
+        // `.a`'s color is replaced by `.b, .a`, whose own color only loses for `.b`
+        assert_eq!(ranges(&diagnostics, "shadowed-declaration"), ["1:4-15"]);
+
+        let hint = &diagnostics[0];
+        assert_eq!(hint.severity, Some(DiagnosticSeverity::HINT));
+        let related = hint.related_information.as_ref().unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].location.range.start, Position::new(4, 9));
+    }
+
+    #[test]
+    fn shadowed_declarations_need_every_selector_overridden() {
+        let text = ".a, .b { color: red; }\n.a { color: blue; }\n";
+        assert!(ranges(&diagnostics(text), "shadowed-declaration").is_empty());
+
+        let text = ".a, .b { color: red; }\n.a { color: blue; }\n.b { color: green; }\n";
+        assert_eq!(
+            ranges(&diagnostics(text), "shadowed-declaration"),
+            ["0:9-20"]
+        );
+    }
+
+    const IDENTICAL: &str = ".a {\n    color: red;\n    margin: 0 4px;\n}\n\
+                             .b {\n    margin: 0 4px;\n    color: red;\n}\n\
+                             .c { color:red; /* same */ margin:  0   4px; }\n\
+                             .d { color: red; margin: 0 5px; }\n";
+
+    #[test]
+    fn identical_rule_bodies_hinted() {
+        let diagnostics = diagnostics_with(IDENTICAL, &enabling("identical-rule-body"));
+        assert_eq!(ranges(&diagnostics, "identical-rule-body"), ["8:0-2"]);
+
+        let hint = &diagnostics[0];
+        assert_eq!(hint.severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(
+            hint.message,
+            "Same declarations as '.a'; the rules can be merged into one"
+        );
+        let related = hint.related_information.as_ref().unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].location.range.start, Position::new(0, 0));
+    }
+
+    #[test]
+    fn identical_rule_bodies_opt_in() {
+        assert!(ranges(&diagnostics(IDENTICAL), "identical-rule-body").is_empty());
+    }
+
+    #[test]
+    fn transform_origin_checked_in_compact_rules() {
+        let diagnostics =
+            diagnostics(".a{transform-origin:left right;}.b{transform-origin:top left;}");
+        assert_eq!(
+            ranges(&diagnostics, "invalid-transform-origin"),
+            ["0:25-30"]
+        );
+    }
+
+    #[test]
+    fn transform_origin_checked_in_wrapped_values() {
+        let diagnostics =
+            diagnostics(".a {\n    transform-origin:\n        10px\n        left;\n}\n");
+        assert_eq!(ranges(&diagnostics, "invalid-transform-origin"), ["3:8-12"]);
+    }
+
+    #[test]
+    fn transform_origin_accepts_valid_values() {
+        let diagnostics =
+            diagnostics(".a { transform-origin: left 10px; }\n.b { transform-origin: 50%; }");
+        assert!(ranges(&diagnostics, "invalid-transform-origin").is_empty());
+    }
+
+    #[test]
+    fn transform_origin_check_can_be_disabled() {
+        let mut settings = Settings::default();
+        settings
+            .diagnostics
+            .rules
+            .insert("invalid-transform-origin".to_string(), false);
+        let diagnostics = diagnostics_with(".a { transform-origin: left right; }", &settings);
+        assert!(ranges(&diagnostics, "invalid-transform-origin").is_empty());
+    }
+
+    fn runtime() -> Settings {
+        Settings {
+            target_context: TargetContext::Runtime,
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn editor_only_elements_flagged_in_runtime_selectors() {
+        let diagnostics = diagnostics_with(".panel > ColorField, Button{color:red;}", &runtime());
+        assert_eq!(ranges(&diagnostics, "unavailable-element"), ["0:9-19"]);
+        let message = &diagnostics
+            .iter()
+            .find(|d| d.code == Some(NumberOrString::String("unavailable-element".into())))
+            .unwrap()
+            .message;
+        assert!(message.contains("'ColorField'"));
+        assert!(message.contains("targetContext: runtime"));
+    }
+
+    #[test]
+    fn editor_only_elements_ignored_in_comments_names_and_values() {
+        let text = "/* ColorField */ .ColorField:hover {\n    --field: ColorField;\n}\n";
+        let diagnostics = diagnostics_with(text, &runtime());
+        assert!(ranges(&diagnostics, "unavailable-element").is_empty());
+    }
+
+    #[test]
+    fn editor_only_elements_allowed_outside_runtime_or_when_disabled() {
+        let diagnostics = diagnostics(".panel > ColorField {}");
+        assert!(ranges(&diagnostics, "unavailable-element").is_empty());
+
+        let mut settings = runtime();
+        settings
+            .diagnostics
+            .rules
+            .insert("unavailable-element".to_string(), false);
+        let diagnostics = diagnostics_with(".panel > ColorField {}", &settings);
+        assert!(ranges(&diagnostics, "unavailable-element").is_empty());
+    }
+}
@@ -0,0 +1,214 @@
+//! Diagnostics queue for USS Language Server
+//!
+//! Computes and publishes diagnostics on blocking threads with bounded
+//! concurrency, coalescing repeated requests for the same document so that a
+//! burst of changes (e.g. a branch switch) does not stall interactive requests.
+//! Edits wait in a lane of their own, so the document being typed in is never
+//! queued behind a backlog of opened files or a workspace-wide refresh.
+
+use crate::analysis;
+use crate::document::Document;
+use crate::health::panic_message;
+use crate::settings::Settings;
+use crate::workspace::{workspace_variables, WorkspaceIndex};
+use dashmap::DashMap;
+use log::error;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::Semaphore;
+use tower_lsp::lsp_types::{MessageType, Url};
+use tower_lsp::Client;
+
+/// Text making the diagnostics of a document panic, to test recovery
+#[cfg(feature = "test-hooks")]
+pub const TEST_PANIC_MARKER: &str = "uss.test.panic";
+
+/// Which lane a diagnostics run waits in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Lane {
+    /// The document was edited; one run at a time, ahead of background work
+    Edit,
+    /// The document was opened or saved, or the workspace or settings changed
+    Background,
+}
+
+/// Queue of documents waiting for diagnostics publication
+pub struct DiagnosticsQueue {
+    client: Client,
+    documents: Arc<DashMap<String, Document>>,
+    settings: Arc<RwLock<Settings>>,
+    index: Arc<WorkspaceIndex>,
+    /// URIs with a run scheduled in a lane that has not started yet
+    pending: Arc<Mutex<HashSet<(String, Lane)>>>,
+    /// Generation of the latest run scheduled for each URI; only a run still
+    /// holding the latest generation publishes
+    generations: Arc<DashMap<String, u64>>,
+    next_generation: AtomicU64,
+    /// Limits how many edited documents are processed at once
+    edit_permits: Arc<Semaphore>,
+    /// Limits how many documents are processed at once in the background
+    permits: RwLock<Arc<Semaphore>>,
+    concurrency: Mutex<usize>,
+}
+
+impl DiagnosticsQueue {
+    pub fn new(
+        client: Client,
+        documents: Arc<DashMap<String, Document>>,
+        settings: Arc<RwLock<Settings>>,
+        index: Arc<WorkspaceIndex>,
+    ) -> Self {
+        let concurrency = settings.read().unwrap().diagnostics.max_concurrency.max(1);
+        Self {
+            client,
+            documents,
+            settings,
+            index,
+            pending: Arc::new(Mutex::new(HashSet::new())),
+            generations: Arc::new(DashMap::new()),
+            next_generation: AtomicU64::new(0),
+            edit_permits: Arc::new(Semaphore::new(1)),
+            permits: RwLock::new(Arc::new(Semaphore::new(concurrency))),
+            concurrency: Mutex::new(concurrency),
+        }
+    }
+
+    /// Apply the concurrency limit from the current settings
+    ///
+    /// Runs already in flight keep their permits from the previous limit.
+    pub fn update_concurrency(&self) {
+        let concurrency = self
+            .settings
+            .read()
+            .unwrap()
+            .diagnostics
+            .max_concurrency
+            .max(1);
+        let mut current = self.concurrency.lock().unwrap();
+        if *current != concurrency {
+            *current = concurrency;
+            *self.permits.write().unwrap() = Arc::new(Semaphore::new(concurrency));
+        }
+    }
+
+    /// Schedule diagnostics publication for every open document
+    pub fn schedule_all(&self) {
+        for entry in self.documents.iter() {
+            self.schedule(entry.key());
+        }
+    }
+
+    /// Schedule diagnostics publication for a document in the background
+    pub fn schedule(&self, uri: &str) {
+        self.schedule_in(uri, Lane::Background);
+    }
+
+    /// Schedule diagnostics publication for a document that was just edited
+    ///
+    /// The run does not wait for background runs, only for other edits.
+    pub fn schedule_edit(&self, uri: &str) {
+        self.schedule_in(uri, Lane::Edit);
+    }
+
+    /// Schedule diagnostics publication for a document in a lane
+    ///
+    /// If a run for the same URI is already waiting in the lane, no new run is
+    /// started: that run reads the latest document, settings, and index when it
+    /// starts. Runs started earlier are superseded and will not publish.
+    fn schedule_in(&self, uri: &str, lane: Lane) {
+        let generation = self.next_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.generations.insert(uri.to_string(), generation);
+        if !self.pending.lock().unwrap().insert((uri.to_string(), lane)) {
+            return;
+        }
+
+        let uri = uri.to_string();
+        let client = self.client.clone();
+        let documents = Arc::clone(&self.documents);
+        let settings = Arc::clone(&self.settings);
+        let index = Arc::clone(&self.index);
+        let pending = Arc::clone(&self.pending);
+        let generations = Arc::clone(&self.generations);
+        let permits = match lane {
+            Lane::Edit => Arc::clone(&self.edit_permits),
+            Lane::Background => Arc::clone(&self.permits.read().unwrap()),
+        };
+
+        tokio::spawn(async move {
+            let Ok(_permit) = permits.acquire_owned().await else {
+                return;
+            };
+
+            // Later changes must schedule a new run from here on; this run
+            // covers every schedule up to now
+            let generation = {
+                let mut pending = pending.lock().unwrap();
+                pending.remove(&(uri.clone(), lane));
+                generations.get(&uri).map(|g| *g)
+            };
+
+            // Snapshot the document so the map is not locked while computing
+            let Some(doc) = documents.get(&uri).map(|d| d.clone()) else {
+                return;
+            };
+            let Ok(url) = uri.parse::<Url>() else {
+                return;
+            };
+            let settings = settings.read().unwrap().clone();
+            let version = doc.version;
+
+            // The analysis is CPU-bound; keep it off the threads serving requests
+            let analysis = tokio::task::spawn_blocking({
+                let documents = Arc::clone(&documents);
+                let uri = uri.clone();
+                let url = url.clone();
+                move || {
+                    #[cfg(feature = "test-hooks")]
+                    if doc.get_text().contains(TEST_PANIC_MARKER) {
+                        panic!("{} found in {}", TEST_PANIC_MARKER, uri);
+                    }
+                    let variables = workspace_variables(&index, &documents, &uri);
+                    analysis::diagnostics(&doc, &url, &settings, &variables)
+                }
+            });
+            let diagnostics = match analysis.await {
+                Ok(diagnostics) => diagnostics,
+                Err(e) => {
+                    // Keep the previously published diagnostics; the panic hook
+                    // has already logged the backtrace
+                    let message = if e.is_panic() {
+                        panic_message(e.into_panic().as_ref()).to_string()
+                    } else {
+                        e.to_string()
+                    };
+                    error!("Diagnostics panicked for {}: {}", uri, message);
+                    client
+                        .show_message(
+                            MessageType::ERROR,
+                            format!(
+                                "USS: internal error in diagnostics for {}: {}",
+                                uri, message
+                            ),
+                        )
+                        .await;
+                    return;
+                }
+            };
+
+            // A later schedule has its own run, which may see a newer version,
+            // settings, or index; don't publish stale results
+            if generations.get(&uri).map(|g| *g) != generation {
+                return;
+            }
+
+            let diagnostics = diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.into_lsp(&url))
+                .collect();
+            client
+                .publish_diagnostics(url, diagnostics, Some(version))
+                .await;
+        });
+    }
+}
@@ -2,29 +2,44 @@
 //!
 //! Manages document state, text operations, and document-related LSP features.
 
+use crate::parser::parse;
+use crate::position_codec::PositionCodec;
+use crate::settings::{BraceStyle, FormatSettings, Settings};
+use crate::tokenizer::{tokenize, Token, TokenKind};
+use crate::uss_data::USS_COLORS;
+use lsp_types::*;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use ropey::Rope;
-use tower_lsp::lsp_types::*;
+use std::collections::{HashMap, HashSet};
 
 /// Represents an open USS document
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Document {
     /// The document content as a rope for efficient editing
     pub content: Rope,
     /// Document version for sync
     pub version: i32,
+    /// Encoding of position columns negotiated with the client
+    pub codec: PositionCodec,
 }
 
 impl Document {
-    /// Create a new document from text content
+    /// Create a new document from text content, using UTF-16 positions
     pub fn new(text: String, version: i32) -> Self {
         Self {
             content: Rope::from_str(&text),
             version,
+            codec: PositionCodec::default(),
         }
     }
 
+    /// Use a different position encoding
+    pub fn with_codec(mut self, codec: PositionCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
     /// Set the entire document content
     pub fn set_content(&mut self, text: String) {
         self.content = Rope::from_str(&text);
@@ -43,61 +58,55 @@ impl Document {
 
     /// Convert a position to a character offset
     pub fn position_to_offset(&self, position: Position) -> Option<usize> {
-        let line = position.line as usize;
-        if line >= self.content.len_lines() {
-            return None;
-        }
-
-        let line_start = self.content.line_to_char(line);
-        let col = position.character as usize;
-        let line_len = self.content.line(line).len_chars();
-
-        if col > line_len {
-            Some(line_start + line_len)
-        } else {
-            Some(line_start + col)
-        }
+        let offset = self.position_to_byte_offset(position)?;
+        Some(self.content.byte_to_char(offset))
     }
 
     /// Convert a character offset to a position
     pub fn offset_to_position(&self, offset: usize) -> Position {
-        let line = self.content.char_to_line(offset);
-        let line_start = self.content.line_to_char(line);
-        let character = offset - line_start;
+        let offset = offset.min(self.content.len_chars());
+        self.byte_offset_to_position(self.content.char_to_byte(offset))
+    }
+
+    /// Convert a position to a byte offset in the document text
+    ///
+    /// Columns past the end of the line clamp to before its terminator.
+    pub fn position_to_byte_offset(&self, position: Position) -> Option<usize> {
+        let line = self.get_line(position.line)?;
+        let line_start = self.content.line_to_byte(position.line as usize);
+        Some(line_start + self.codec.byte_offset(&line, position.character))
+    }
+
+    /// Convert a byte offset in the document text to a position
+    pub fn byte_offset_to_position(&self, byte_offset: usize) -> Position {
+        let byte_offset = byte_offset.min(self.content.len_bytes());
+        let line = self.content.byte_to_line(byte_offset);
+        let line_start = self.content.line_to_byte(line);
+        let line_text = self.get_line(line as u32).unwrap_or_default();
 
         Position {
             line: line as u32,
-            character: character as u32,
+            character: self.codec.column(&line_text, byte_offset - line_start),
         }
     }
 
     /// Get the word at a position
     pub fn get_word_at_position(&self, position: Position) -> Option<String> {
-        let offset = self.position_to_offset(position)?;
+        let offset = self.position_to_byte_offset(position)?;
         let text = self.content.to_string();
-        let bytes = text.as_bytes();
+        let is_word_char = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
 
         // Find word boundaries
-        let mut start = offset;
-        let mut end = offset;
-
-        // Search backward for word start
-        while start > 0 {
-            let c = bytes[start - 1] as char;
-            if !c.is_alphanumeric() && c != '-' && c != '_' {
-                break;
-            }
-            start -= 1;
-        }
-
-        // Search forward for word end
-        while end < bytes.len() {
-            let c = bytes[end] as char;
-            if !c.is_alphanumeric() && c != '-' && c != '_' {
-                break;
-            }
-            end += 1;
-        }
+        let start = text[..offset]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_word_char(*c))
+            .last()
+            .map_or(offset, |(i, _)| i);
+        let end = text[offset..]
+            .char_indices()
+            .find(|(_, c)| !is_word_char(*c))
+            .map_or(text.len(), |(i, _)| offset + i);
 
         if start < end {
             Some(text[start..end].to_string())
@@ -106,8 +115,19 @@ impl Document {
         }
     }
 
-    /// Get the line at a position
+    /// Get the text of a line, without its line terminator
     pub fn get_line(&self, line: u32) -> Option<String> {
+        let mut text = self.get_line_with_terminator(line)?;
+        if text.ends_with("\r\n") {
+            text.truncate(text.len() - 2);
+        } else if text.ends_with(is_line_break) {
+            text.pop();
+        }
+        Some(text)
+    }
+
+    /// Get the text of a line, including its line terminator if it has one
+    pub fn get_line_with_terminator(&self, line: u32) -> Option<String> {
         let line = line as usize;
         if line >= self.content.len_lines() {
             return None;
@@ -115,16 +135,13 @@ impl Document {
         Some(self.content.line(line).to_string())
     }
 
-    #[allow(dead_code)]
     /// Get the text before the cursor on the current line
+    ///
+    /// The column is converted with the document's position encoding.
     pub fn get_text_before_cursor(&self, position: Position) -> Option<String> {
-        let line_text = self.get_line(position.line)?;
-        let col = position.character as usize;
-        if col > line_text.len() {
-            Some(line_text)
-        } else {
-            Some(line_text[..col].to_string())
-        }
+        let mut line_text = self.get_line(position.line)?;
+        line_text.truncate(self.codec.byte_offset(&line_text, position.character));
+        Some(line_text)
     }
 
     /// Get the full document text
@@ -138,10 +155,22 @@ impl Document {
     }
 }
 
+/// Check whether a character ends a line (the line breaks recognized by the rope)
+fn is_line_break(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r' | '\u{000B}' | '\u{000C}' | '\u{0085}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
 /// Format an entire USS document
-pub fn format_document(doc: &Document, options: &FormattingOptions) -> Vec<TextEdit> {
+pub fn format_document(
+    doc: &Document,
+    options: &FormattingOptions,
+    settings: FormatSettings,
+) -> Vec<TextEdit> {
     let text = doc.get_text();
-    let formatted = format_uss(&text, options);
+    let formatted = format_uss(&text, options, settings);
 
     if formatted == text {
         return vec![];
@@ -160,15 +189,23 @@ pub fn format_document(doc: &Document, options: &FormattingOptions) -> Vec<TextE
 }
 
 /// Format a range of a USS document
-pub fn format_range(doc: &Document, range: Range, options: &FormattingOptions) -> Vec<TextEdit> {
+pub fn format_range(
+    doc: &Document,
+    range: Range,
+    options: &FormattingOptions,
+    settings: FormatSettings,
+) -> Vec<TextEdit> {
     let start_offset = doc.position_to_offset(range.start).unwrap_or(0);
     let end_offset = doc
         .position_to_offset(range.end)
         .unwrap_or(doc.content.len_chars());
 
-    let text = doc.get_text();
-    let slice = &text[start_offset..end_offset];
-    let formatted = format_uss(slice, options);
+    let slice = doc
+        .content
+        .slice(start_offset..end_offset.max(start_offset))
+        .to_string();
+    let slice = slice.as_str();
+    let formatted = format_uss(slice, options, settings);
 
     if formatted == slice {
         return vec![];
@@ -180,8 +217,20 @@ pub fn format_range(doc: &Document, range: Range, options: &FormattingOptions) -
     }]
 }
 
+/// Get the formatted text of an entire USS document
+pub fn formatted_text(
+    doc: &Document,
+    options: &FormattingOptions,
+    settings: FormatSettings,
+) -> String {
+    format_uss(&doc.get_text(), options, settings)
+}
+
 /// Format USS content
-fn format_uss(text: &str, options: &FormattingOptions) -> String {
+///
+/// `settings` are overridden by recognized keys in `options.properties`.
+fn format_uss(text: &str, options: &FormattingOptions, settings: FormatSettings) -> String {
+    let settings = settings.with_overrides(&options.properties);
     let indent = if options.insert_spaces {
         " ".repeat(options.tab_size as usize)
     } else {
@@ -209,9 +258,22 @@ fn format_uss(text: &str, options: &FormattingOptions) -> String {
 
         match c {
             '{' => {
-                // Ensure space before brace
-                if !result.ends_with(' ') && !result.ends_with('\n') {
-                    result.push(' ');
+                match settings.brace_style {
+                    BraceStyle::SameLine => {
+                        // Ensure space before brace
+                        if !result.ends_with(' ') && !result.ends_with('\n') {
+                            result.push(' ');
+                        }
+                    }
+                    BraceStyle::NextLine => {
+                        while result.ends_with(' ') || result.ends_with('\t') {
+                            result.pop();
+                        }
+                        if !result.is_empty() && !result.ends_with('\n') {
+                            result.push('\n');
+                        }
+                        result.push_str(&indent.repeat(indent_level));
+                    }
                 }
                 result.push(c);
                 result.push('\n');
@@ -279,6 +341,9 @@ static VAR_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"--[\w-]+").unwrap());
 static VAR_USAGE_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"var\s*\(\s*(--[\w-]+)\s*\)").unwrap());
 
+/// Regex for matching variable definitions (`--name:`)
+static VAR_DEFINITION_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(--[\w-]+)\s*:").unwrap());
+
 #[allow(dead_code)]
 /// Regex for matching class selectors
 static CLASS_SELECTOR_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\.[\w-]+").unwrap());
@@ -295,6 +360,38 @@ static RGBA_COLOR_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"rgba?\s*\(\s*(\d+)\s*,\s*(\d+)\s*,\s*(\d+)\s*(?:,\s*([\d.]+)\s*)?\)").unwrap()
 });
 
+/// Collect the names of all variables defined in a text
+pub fn defined_variables(text: &str) -> HashSet<String> {
+    VAR_DEFINITION_REGEX
+        .captures_iter(text)
+        .filter_map(|cap| cap.get(1))
+        .map(|m| m.as_str().to_string())
+        .collect()
+}
+
+/// Count var() usages of each variable in a text, ignoring comments
+pub fn variable_usages(text: &str) -> HashMap<String, usize> {
+    let tokens: Vec<Token> = tokenize(text)
+        .into_iter()
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .collect();
+    let mut usages = HashMap::new();
+
+    for window in tokens.windows(3) {
+        let [function, paren, name] = [window[0], window[1], window[2]];
+        if function.kind == TokenKind::Ident
+            && function.text(text) == "var"
+            && paren.kind == TokenKind::Delim('(')
+            && name.kind == TokenKind::Ident
+            && name.text(text).starts_with("--")
+        {
+            *usages.entry(name.text(text).to_string()).or_insert(0) += 1;
+        }
+    }
+
+    usages
+}
+
 /// Find definition of a variable or selector
 pub fn find_definition(doc: &Document, position: Position, uri: &str) -> Option<Location> {
     let word = doc.get_word_at_position(position)?;
@@ -306,8 +403,8 @@ pub fn find_definition(doc: &Document, position: Position, uri: &str) -> Option<
         let pattern = format!(r"({})\s*:", regex::escape(&word));
         if let Ok(re) = Regex::new(&pattern) {
             if let Some(m) = re.find(&text) {
-                let start = doc.offset_to_position(m.start());
-                let end = doc.offset_to_position(m.end() - 1); // Exclude colon
+                let start = doc.byte_offset_to_position(m.start());
+                let end = doc.byte_offset_to_position(m.end() - 1); // Exclude colon
 
                 return Some(Location {
                     uri: uri.parse().ok()?,
@@ -322,8 +419,8 @@ pub fn find_definition(doc: &Document, position: Position, uri: &str) -> Option<
         let pattern = format!(r"({})\s*\{{", regex::escape(&word));
         if let Ok(re) = Regex::new(&pattern) {
             if let Some(m) = re.find(&text) {
-                let start = doc.offset_to_position(m.start());
-                let end = doc.offset_to_position(m.start() + word.len());
+                let start = doc.byte_offset_to_position(m.start());
+                let end = doc.byte_offset_to_position(m.start() + word.len());
 
                 return Some(Location {
                     uri: uri.parse().ok()?,
@@ -337,57 +434,202 @@ pub fn find_definition(doc: &Document, position: Position, uri: &str) -> Option<
 }
 
 /// Find all references to a variable or selector
-pub fn find_references(doc: &Document, position: Position, uri: &str) -> Vec<Location> {
+///
+/// Classes and IDs are only matched in selectors and, as `.name` or `#name`,
+/// in comments. Mentions inside comments are skipped unless
+/// `references.includeComments` is enabled, in which case they are returned
+/// after all code results.
+pub fn find_references(
+    doc: &Document,
+    position: Position,
+    uri: &str,
+    settings: &Settings,
+) -> Vec<Location> {
     let mut refs = Vec::new();
+    let mut comment_refs = Vec::new();
+    let text = doc.get_text();
+    let url: Url = match uri.parse() {
+        Ok(url) => url,
+        Err(_) => return refs,
+    };
+    let location = |start: usize, end: usize| Location {
+        uri: url.clone(),
+        range: Range {
+            start: doc.byte_offset_to_position(start),
+            end: doc.byte_offset_to_position(end),
+        },
+    };
+    let comments: Vec<Token> = tokenize(&text)
+        .into_iter()
+        .filter(|t| t.is_comment())
+        .collect();
+    let in_comment =
+        |start: usize, end: usize| comments.iter().any(|c| c.start <= start && end <= c.end);
+
+    if let Some((mention, occurrences)) = doc
+        .position_to_byte_offset(position)
+        .and_then(|offset| selector_name_occurrences(&text, offset))
+    {
+        refs.extend(
+            occurrences
+                .into_iter()
+                .map(|(start, end)| location(start, end)),
+        );
+        if settings.references.include_comments {
+            // Like selector matches, the range excludes the prefix
+            refs.extend(
+                name_occurrences(&text, &mention)
+                    .into_iter()
+                    .filter(|&(start, end)| in_comment(start, end))
+                    .map(|(start, end)| location(start + 1, end)),
+            );
+        }
+        return refs;
+    }
+
     let word = match doc.get_word_at_position(position) {
         Some(w) => w,
         None => return refs,
     };
-    let text = doc.get_text();
 
-    // Find all occurrences of the word
-    let pattern = regex::escape(&word);
-    if let Ok(re) = Regex::new(&format!(r"\b{}\b", pattern)) {
-        for m in re.find_iter(&text) {
-            let start = doc.offset_to_position(m.start());
-            let end = doc.offset_to_position(m.end());
+    // Find all occurrences of the word, except as a class or ID name
+    for (start, end) in name_occurrences(&text, &word) {
+        if text[..start].ends_with(['.', '#']) {
+            continue;
+        }
+        let location = location(start, end);
 
-            if let Ok(url) = uri.parse() {
-                refs.push(Location {
-                    uri: url,
-                    range: Range { start, end },
-                });
-            }
+        if !in_comment(start, end) {
+            refs.push(location);
+        } else if settings.references.include_comments {
+            comment_refs.push(location);
         }
     }
 
+    refs.extend(comment_refs);
     refs
 }
 
 /// Rename a variable or selector
+///
+/// Classes and IDs are renamed in selectors only; a leading `.` or `#` in the
+/// new name is ignored.
 pub fn rename(
     doc: &Document,
     position: Position,
     new_name: &str,
     uri: &str,
 ) -> Option<WorkspaceEdit> {
+    let text = doc.get_text();
+    let offset = doc.position_to_byte_offset(position)?;
+    if let Some((_, occurrences)) = selector_name_occurrences(&text, offset) {
+        let new_name = new_name.trim_start_matches(['.', '#']);
+        return rename_occurrences(doc, &occurrences, new_name, uri);
+    }
+
     let word = doc.get_word_at_position(position)?;
+    let occurrences = name_occurrences(&text, &word);
+    rename_occurrences(doc, &occurrences, new_name, uri)
+}
+
+/// Byte ranges of the class or ID at an offset, if the offset is on one in a selector
+///
+/// Only names in selectors are returned, so a value keyword or comment with the
+/// same word never matches. Classes and IDs are distinct even if equally named.
+/// The name is returned with its prefix, as in `.toolbar`.
+fn selector_name_occurrences(text: &str, offset: usize) -> Option<(String, Vec<(usize, usize)>)> {
+    let names = parse(text).selector_names();
+    // The cursor may be on the prefix
+    let target = names
+        .iter()
+        .find(|n| n.start - 1 <= offset && offset <= n.end)?;
+    Some((
+        format!("{}{}", target.prefix, target.name),
+        names
+            .iter()
+            .filter(|n| n.prefix == target.prefix && n.name == target.name)
+            .map(|n| (n.start, n.end))
+            .collect(),
+    ))
+}
+
+/// Rename the occurrences of a name inside a range
+///
+/// Occurrences are matched like [`rename`]: `.name` and `#name`, or a bare
+/// name used as a class or ID in a selector, are renamed in selectors only;
+/// other names match whole words. Occurrences not entirely inside the range
+/// are left alone.
+pub fn rename_in_range(
+    doc: &Document,
+    range: Range,
+    old_name: &str,
+    new_name: &str,
+    uri: &str,
+) -> Option<WorkspaceEdit> {
+    let start = doc.position_to_byte_offset(range.start)?;
+    let end = doc.position_to_byte_offset(range.end)?;
     let text = doc.get_text();
 
-    let mut edits = Vec::new();
-    let pattern = regex::escape(&word);
+    let prefix = old_name.chars().next().filter(|c| matches!(c, '.' | '#'));
+    let name = old_name.trim_start_matches(['.', '#']);
+    let selector_names: Vec<(usize, usize)> = parse(&text)
+        .selector_names()
+        .into_iter()
+        .filter(|n| n.name == name && prefix.is_none_or(|prefix| n.prefix == prefix))
+        .map(|n| (n.start, n.end))
+        .collect();
+    let (occurrences, new_name) = if prefix.is_some() || !selector_names.is_empty() {
+        (selector_names, new_name.trim_start_matches(['.', '#']))
+    } else {
+        (name_occurrences(&text, old_name), new_name)
+    };
 
-    if let Ok(re) = Regex::new(&format!(r"\b{}\b", pattern)) {
-        for m in re.find_iter(&text) {
-            let start = doc.offset_to_position(m.start());
-            let end = doc.offset_to_position(m.end());
+    let occurrences: Vec<(usize, usize)> = occurrences
+        .into_iter()
+        .filter(|&(s, e)| start <= s && e <= end)
+        .collect();
+    rename_occurrences(doc, &occurrences, new_name, uri)
+}
 
-            edits.push(TextEdit {
-                range: Range { start, end },
-                new_text: new_name.to_string(),
-            });
-        }
+/// Byte ranges of the occurrences of a name that are not part of a longer name
+///
+/// Names may contain `-` and `_`, so `item` matches neither `.item-list` nor
+/// `--item`.
+pub fn name_occurrences(text: &str, name: &str) -> Vec<(usize, usize)> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() {
+        return Vec::new();
     }
+    let check_start = name.starts_with(is_word_char);
+    let check_end = name.ends_with(is_word_char);
+
+    text.match_indices(name)
+        .map(|(start, _)| (start, start + name.len()))
+        .filter(|&(start, end)| {
+            let joined_before = check_start && text[..start].ends_with(is_word_char);
+            let joined_after = check_end && text[end..].starts_with(is_word_char);
+            !joined_before && !joined_after
+        })
+        .collect()
+}
+
+/// Build the workspace edit replacing occurrences with a new name
+fn rename_occurrences(
+    doc: &Document,
+    occurrences: &[(usize, usize)],
+    new_name: &str,
+    uri: &str,
+) -> Option<WorkspaceEdit> {
+    let edits: Vec<TextEdit> = occurrences
+        .iter()
+        .map(|&(start, end)| TextEdit {
+            range: Range {
+                start: doc.byte_offset_to_position(start),
+                end: doc.byte_offset_to_position(end),
+            },
+            new_text: new_name.to_string(),
+        })
+        .collect();
 
     if edits.is_empty() {
         return None;
@@ -405,61 +647,113 @@ pub fn rename(
 }
 
 /// Extract colors from the document
+///
+/// Only declaration values are searched, so hex-like ID selectors (`#badcaf`)
+/// and variable names (`--c0ffee`) never get a color.
 pub fn get_colors(doc: &Document) -> Vec<ColorInformation> {
     let mut colors = Vec::new();
     let text = doc.get_text();
 
-    // Find hex colors
-    for cap in HEX_COLOR_REGEX.captures_iter(&text) {
-        if let Some(m) = cap.get(0) {
-            let hex = cap.get(1).map(|c| c.as_str()).unwrap_or("");
-            if let Some(color) = parse_hex_color(hex) {
-                let start = doc.offset_to_position(m.start());
-                let end = doc.offset_to_position(m.end());
+    for decl in parse(&text)
+        .rules
+        .iter()
+        .flat_map(|rule| &rule.declarations)
+    {
+        let value = &text[decl.value_start..decl.value_end];
+        let range = |start: usize, end: usize| Range {
+            start: doc.byte_offset_to_position(decl.value_start + start),
+            end: doc.byte_offset_to_position(decl.value_start + end),
+        };
+
+        // Find hex colors
+        for cap in HEX_COLOR_REGEX.captures_iter(value) {
+            if let Some(m) = cap.get(0) {
+                let hex = cap.get(1).map(|c| c.as_str()).unwrap_or("");
+                if let Some(color) = parse_hex_color(hex) {
+                    colors.push(ColorInformation {
+                        range: range(m.start(), m.end()),
+                        color,
+                    });
+                }
+            }
+        }
+
+        // Find rgb/rgba colors
+        for cap in RGBA_COLOR_REGEX.captures_iter(value) {
+            if let Some(m) = cap.get(0) {
+                let r: f32 = cap
+                    .get(1)
+                    .and_then(|c| c.as_str().parse().ok())
+                    .unwrap_or(0.0);
+                let g: f32 = cap
+                    .get(2)
+                    .and_then(|c| c.as_str().parse().ok())
+                    .unwrap_or(0.0);
+                let b: f32 = cap
+                    .get(3)
+                    .and_then(|c| c.as_str().parse().ok())
+                    .unwrap_or(0.0);
+                let a: f32 = cap
+                    .get(4)
+                    .and_then(|c| c.as_str().parse().ok())
+                    .unwrap_or(1.0);
+
                 colors.push(ColorInformation {
-                    range: Range { start, end },
-                    color,
+                    range: range(m.start(), m.end()),
+                    color: Color {
+                        red: r / 255.0,
+                        green: g / 255.0,
+                        blue: b / 255.0,
+                        alpha: a,
+                    },
                 });
             }
         }
     }
 
-    // Find rgb/rgba colors
-    for cap in RGBA_COLOR_REGEX.captures_iter(&text) {
-        if let Some(m) = cap.get(0) {
-            let r: f32 = cap
-                .get(1)
-                .and_then(|c| c.as_str().parse().ok())
-                .unwrap_or(0.0);
-            let g: f32 = cap
-                .get(2)
-                .and_then(|c| c.as_str().parse().ok())
-                .unwrap_or(0.0);
-            let b: f32 = cap
-                .get(3)
-                .and_then(|c| c.as_str().parse().ok())
-                .unwrap_or(0.0);
-            let a: f32 = cap
-                .get(4)
-                .and_then(|c| c.as_str().parse().ok())
-                .unwrap_or(1.0);
-
-            let start = doc.offset_to_position(m.start());
-            let end = doc.offset_to_position(m.end());
-
-            colors.push(ColorInformation {
-                range: Range { start, end },
-                color: Color {
-                    red: r / 255.0,
-                    green: g / 255.0,
-                    blue: b / 255.0,
-                    alpha: a,
-                },
-            });
-        }
+    colors
+}
+
+/// Parse a color written as hex (`#FF3366`), `rgb()`, `rgba()`, or a USS color name
+pub fn parse_color(text: &str) -> Option<Color> {
+    let text = text.trim();
+    if let Some(hex) = text.strip_prefix('#') {
+        return parse_hex_color(hex);
+    }
+    if let Some((_, hex)) = USS_COLORS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(text))
+    {
+        return parse_hex_color(hex);
     }
 
-    colors
+    let cap = RGBA_COLOR_REGEX.captures(text)?;
+    if cap.get(0)?.as_str() != text {
+        return None;
+    }
+    let channel = |i: usize| -> Option<f32> { cap.get(i)?.as_str().parse().ok() };
+    Some(Color {
+        red: channel(1)? / 255.0,
+        green: channel(2)? / 255.0,
+        blue: channel(3)? / 255.0,
+        alpha: channel(4).unwrap_or(1.0),
+    })
+}
+
+/// Normalized hex form of a color (`#FF3366`, or `#FF336680` when translucent)
+pub fn color_hex(color: Color) -> String {
+    let [r, g, b, a] = color_channels(color);
+    if a == 255 {
+        format!("#{:02X}{:02X}{:02X}", r, g, b)
+    } else {
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+    }
+}
+
+/// Red, green, blue, and alpha of a color, each from 0 to 255
+pub fn color_channels(color: Color) -> [u8; 4] {
+    [color.red, color.green, color.blue, color.alpha]
+        .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
 }
 
 /// Parse a hex color string to LSP Color
@@ -523,28 +817,14 @@ fn parse_hex_color(hex: &str) -> Option<Color> {
 
 /// Get color presentations for a color
 pub fn get_color_presentations(color: Color) -> Vec<ColorPresentation> {
-    let r = (color.red * 255.0).round() as u8;
-    let g = (color.green * 255.0).round() as u8;
-    let b = (color.blue * 255.0).round() as u8;
+    let [r, g, b, _] = color_channels(color);
     let a = color.alpha;
 
-    let mut presentations = Vec::new();
-
-    // Hex format
-    if a >= 1.0 {
-        presentations.push(ColorPresentation {
-            label: format!("#{:02X}{:02X}{:02X}", r, g, b),
-            text_edit: None,
-            additional_text_edits: None,
-        });
-    } else {
-        let a_byte = (a * 255.0).round() as u8;
-        presentations.push(ColorPresentation {
-            label: format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a_byte),
-            text_edit: None,
-            additional_text_edits: None,
-        });
-    }
+    let mut presentations = vec![ColorPresentation {
+        label: color_hex(color),
+        text_edit: None,
+        additional_text_edits: None,
+    }];
 
     // RGB/RGBA format
     if a >= 1.0 {
@@ -563,3 +843,263 @@ pub fn get_color_presentations(color: Color) -> Vec<ColorPresentation> {
 
     presentations
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URI: &str = "file:///theme.uss";
+
+    fn apply(doc: &Document, edit: Option<WorkspaceEdit>) -> String {
+        let mut doc = doc.clone();
+        let Some(mut changes) = edit.and_then(|edit| edit.changes) else {
+            return doc.get_text();
+        };
+        let mut edits = changes.remove(&URI.parse::<Url>().unwrap()).unwrap();
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+        for edit in edits {
+            doc.apply_change(edit.range, &edit.new_text);
+        }
+        doc.get_text()
+    }
+
+    fn range(start_line: u32, end_line: u32) -> Range {
+        Range {
+            start: Position::new(start_line, 0),
+            end: Position::new(end_line, 0),
+        }
+    }
+
+    const HIDDEN: &str =
+        "/* hidden panels */\n.hidden { visibility: hidden; }\n.panel .hidden { display: none; }\n";
+
+    #[test]
+    fn rename_class_skips_value_keywords_and_comments() {
+        let doc = Document::new(HIDDEN.to_string(), 1);
+        let edit = rename(&doc, Position::new(1, 3), ".collapsed", URI);
+        assert_eq!(
+            apply(&doc, edit),
+            "/* hidden panels */\n.collapsed { visibility: hidden; }\n.panel .collapsed { display: none; }\n"
+        );
+    }
+
+    #[test]
+    fn rename_in_range_skips_value_keywords_and_comments() {
+        let doc = Document::new(HIDDEN.to_string(), 1);
+        for old_name in [".hidden", "hidden"] {
+            let edit = rename_in_range(&doc, range(0, 2), old_name, "collapsed", URI);
+            assert_eq!(
+                apply(&doc, edit),
+                "/* hidden panels */\n.collapsed { visibility: hidden; }\n.panel .hidden { display: none; }\n",
+                "{}",
+                old_name
+            );
+        }
+        assert!(rename_in_range(&doc, range(0, 2), "#hidden", "collapsed", URI).is_none());
+    }
+
+    #[test]
+    fn rename_in_range_only_renames_inside_the_range() {
+        let text =
+            ":root { --gap: 4px; }\n.a { margin: var(--gap); }\n.b { padding: var(--gap); }\n";
+        let doc = Document::new(text.to_string(), 1);
+        let edit = rename_in_range(&doc, range(1, 2), "--gap", "--space", URI);
+        assert_eq!(
+            apply(&doc, edit),
+            ":root { --gap: 4px; }\n.a { margin: var(--space); }\n.b { padding: var(--gap); }\n"
+        );
+        assert!(rename_in_range(&doc, range(1, 2), "--missing", "--x", URI).is_none());
+    }
+
+    #[test]
+    fn rename_in_range_matches_whole_names() {
+        let text = ".item, .item-list { color: red; }\n";
+        let doc = Document::new(text.to_string(), 1);
+        let edit = rename_in_range(&doc, range(0, 1), ".item", "entry", URI);
+        assert_eq!(apply(&doc, edit), ".entry, .item-list { color: red; }\n");
+    }
+
+    fn lines(text: &str) -> Vec<(String, String)> {
+        let doc = Document::new(text.to_string(), 1);
+        (0..doc.line_count() as u32)
+            .map(|line| {
+                (
+                    doc.get_line(line).unwrap(),
+                    doc.get_line_with_terminator(line).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(line, full)| (line.to_string(), full.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn lines_strip_crlf() {
+        assert_eq!(
+            lines(".a {\r\n}\r\n"),
+            pairs(&[(".a {", ".a {\r\n"), ("}", "}\r\n"), ("", "")])
+        );
+    }
+
+    #[test]
+    fn lines_strip_lone_cr() {
+        assert_eq!(
+            lines(".a {\r}\rb"),
+            pairs(&[(".a {", ".a {\r"), ("}", "}\r"), ("b", "b")])
+        );
+    }
+
+    #[test]
+    fn lines_strip_lf_at_eof() {
+        assert_eq!(lines(".a {}\n"), pairs(&[(".a {}", ".a {}\n"), ("", "")]));
+        assert_eq!(lines(".a {}"), pairs(&[(".a {}", ".a {}")]));
+
+        let doc = Document::new(".a {}\n".to_string(), 1);
+        assert_eq!(doc.get_line(2), None);
+        assert_eq!(doc.get_line_with_terminator(2), None);
+    }
+
+    /// Start positions (`line:character`) of the references to the name at `|`
+    fn references(text: &str, include_comments: bool) -> Vec<String> {
+        let offset = text.find('|').unwrap();
+        let doc = Document::new(text.replacen('|', "", 1), 1);
+        let mut settings = Settings::default();
+        settings.references.include_comments = include_comments;
+        find_references(&doc, doc.byte_offset_to_position(offset), URI, &settings)
+            .into_iter()
+            .map(|location| {
+                let start = location.range.start;
+                format!("{}:{}", start.line, start.character)
+            })
+            .collect()
+    }
+
+    const COMMENTED: &str = "/* --accent is the brand color */\n\
+                             :root { --acc|ent: red; }\n\
+                             .a { color: var(--accent); /* not --accent-dark */ }\n";
+
+    #[test]
+    fn references_skip_comments_by_default() {
+        assert_eq!(references(COMMENTED, false), ["1:8", "2:16"]);
+    }
+
+    #[test]
+    fn references_include_comment_mentions_after_code() {
+        assert_eq!(references(COMMENTED, true), ["1:8", "2:16", "0:3"]);
+    }
+
+    #[test]
+    fn class_references_include_comment_mentions_when_enabled() {
+        let text = "/* used by .toolbar and #toolbar, not .toolbar-item */\n\
+                    .tool|bar { }\n.toolbar:hover { }\n";
+        assert_eq!(references(text, false), ["1:1", "2:1"]);
+        assert_eq!(references(text, true), ["1:1", "2:1", "0:12"]);
+    }
+
+    /// `row` as a class, an ID, a value keyword, and in a comment
+    const MIXED: &str = "/* .row lays out #row children in a row */\n\
+                         .r|ow { flex-direction: row; }\n\
+                         #r$ow > .row-item { }\n\
+                         .row:hover, #row .row { -unity-text-align: middle-center; }\n";
+
+    /// `MIXED` with the cursor at the marker, removing the other one
+    fn mixed(marker: char) -> String {
+        MIXED
+            .replace(if marker == '|' { '$' } else { '|' }, "")
+            .replace(marker, "|")
+    }
+
+    #[test]
+    fn references_keep_classes_ids_and_keywords_apart() {
+        assert_eq!(references(&mixed('|'), false), ["1:1", "3:1", "3:18"]);
+        assert_eq!(references(&mixed('|'), true), ["1:1", "3:1", "3:18", "0:4"]);
+        assert_eq!(references(&mixed('$'), false), ["2:1", "3:13"]);
+        assert_eq!(references(&mixed('$'), true), ["2:1", "3:13", "0:18"]);
+
+        let keyword = mixed('|')
+            .replacen('|', "", 1)
+            .replacen(": row;", ": r|ow;", 1);
+        assert_eq!(references(&keyword, false), ["1:23"]);
+        assert_eq!(references(&keyword, true), ["1:23", "0:36"]);
+    }
+
+    fn formatted(text: &str, settings: FormatSettings, brace_style: Option<&str>) -> String {
+        let mut options = FormattingOptions {
+            tab_size: 4,
+            insert_spaces: true,
+            ..Default::default()
+        };
+        if let Some(style) = brace_style {
+            options.properties.insert(
+                "uss.format.braceStyle".to_string(),
+                FormattingProperty::String(style.to_string()),
+            );
+        }
+        formatted_text(&Document::new(text.to_string(), 1), &options, settings)
+    }
+
+    #[test]
+    fn format_properties_override_brace_style_per_request() {
+        let text = ".a { color: red; }\n";
+        let same_line = FormatSettings::default();
+        let next_line = FormatSettings {
+            brace_style: BraceStyle::NextLine,
+        };
+
+        assert_eq!(
+            formatted(text, same_line, None),
+            formatted(text, next_line, Some("same-line"))
+        );
+        assert_eq!(
+            formatted(text, same_line, Some("next-line")),
+            formatted(text, next_line, None)
+        );
+        assert_ne!(
+            formatted(text, same_line, None),
+            formatted(text, same_line, Some("next-line"))
+        );
+        assert_eq!(
+            formatted(text, same_line, Some("allman")),
+            formatted(text, same_line, None)
+        );
+    }
+
+    /// Colors found in a document, as `line:start-end`
+    fn color_ranges(text: &str) -> Vec<String> {
+        get_colors(&Document::new(text.to_string(), 1))
+            .into_iter()
+            .map(|info| {
+                let range = info.range;
+                format!(
+                    "{}:{}-{}",
+                    range.start.line, range.start.character, range.end.character
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hex_like_id_selectors_have_no_color() {
+        let text = "#badcaf {\n    color: #FF0000;\n}\n#fff, #c0ffee:hover {\n    border-color: rgb(0, 128, 255);\n}\n";
+        assert_eq!(color_ranges(text), ["1:11-18", "4:18-34"]);
+    }
+
+    #[test]
+    fn variable_names_have_no_color() {
+        let text =
+            ":root {\n    --c0ffee: #00FF00;\n    --fff: red;\n}\n.a { color: var(--c0ffee); }\n";
+        assert_eq!(color_ranges(text), ["1:14-21"]);
+    }
+
+    #[test]
+    fn comments_have_no_color() {
+        let text =
+            "/* #FF0000 */\n.a {\n    /* #00FF00; */\n    color: #0000FF /* was #FFFFFF */;\n}\n";
+        assert_eq!(color_ranges(text), ["3:11-18"]);
+    }
+}
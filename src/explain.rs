@@ -0,0 +1,166 @@
+//! Style explanations for USS Language Server
+//!
+//! Generates Markdown reports describing the rule under the cursor: its
+//! selectors and their specificity, what each declaration does, and which
+//! later rules in the file override it.
+
+use crate::document::Document;
+use crate::parser::{normalize_selector, parse, Rule};
+use crate::tokenizer::{tokenize, TokenKind};
+use crate::uss_data::USS_PROPERTIES;
+use lsp_types::Position;
+use std::fmt::Write;
+
+/// Selector specificity as (ID, class/pseudo-class, type) counts
+pub type Specificity = (u32, u32, u32);
+
+/// Compute the specificity of a single (non-list) selector
+///
+/// USS has no pseudo-elements, so `::name` counts as the pseudo-class it
+/// spells, not as a type.
+pub fn specificity(selector: &str) -> Specificity {
+    let tokens = tokenize(selector);
+    let mut result = (0, 0, 0);
+
+    for (i, token) in tokens.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|p| tokens[p].kind);
+        match token.kind {
+            TokenKind::Hash => result.0 += 1,
+            TokenKind::Ident => match previous {
+                Some(TokenKind::Delim('.')) | Some(TokenKind::Delim(':')) => result.1 += 1,
+                _ => result.2 += 1,
+            },
+            _ => {}
+        }
+    }
+
+    result
+}
+
+/// Explain the rule at a position, or `None` if the position is outside any rule
+pub fn explain_styles(doc: &Document, position: Position) -> Option<String> {
+    let text = doc.get_text();
+    let stylesheet = parse(&text);
+    let offset = doc.position_to_byte_offset(position)?;
+    let rule = stylesheet.rule_at(offset)?;
+    let line_of = |offset: usize| doc.byte_offset_to_position(offset).line + 1;
+
+    let mut report = String::new();
+    let _ = writeln!(report, "# Style explanation: `{}`\n", rule.selector);
+    let _ = writeln!(
+        report,
+        "Defined at line {}.\n",
+        line_of(rule.selector_start)
+    );
+
+    let _ = writeln!(report, "## Selectors\n");
+    let _ = writeln!(report, "| Selector | Specificity (ID, class, type) |");
+    let _ = writeln!(report, "|----------|-------------------------------|");
+    for selector in rule.selectors() {
+        let (a, b, c) = specificity(selector);
+        let _ = writeln!(report, "| `{}` | ({}, {}, {}) |", selector, a, b, c);
+    }
+
+    let _ = writeln!(report, "\n## Declarations\n");
+    if rule.declarations.is_empty() {
+        let _ = writeln!(report, "_No declarations._");
+    }
+    for decl in &rule.declarations {
+        let explanation = if decl.property.starts_with("--") {
+            "Custom property (variable).".to_string()
+        } else if let Some(prop) = USS_PROPERTIES.get(decl.property.as_str()) {
+            format!(
+                "{} Inherited: {}.",
+                prop.description,
+                if prop.inherited { "yes" } else { "no" }
+            )
+        } else {
+            "Unknown property; Unity ignores it.".to_string()
+        };
+        let _ = writeln!(
+            report,
+            "- `{}: {}` — {}",
+            decl.property, decl.value, explanation
+        );
+    }
+
+    let overrides = find_overrides(rule, &stylesheet.rules);
+    if !overrides.is_empty() {
+        let _ = writeln!(report, "\n## Overridden later in this file\n");
+        for (other, property) in overrides {
+            let _ = writeln!(
+                report,
+                "- `{}` is set again by `{}` at line {}",
+                property,
+                other.selector,
+                line_of(other.selector_start)
+            );
+        }
+    }
+
+    Some(report)
+}
+
+/// Find later rules with an identical selector that set the same properties
+///
+/// Selectors are compared after normalization, so `.a>.b` matches `.a > .b`.
+fn find_overrides<'a>(rule: &Rule, rules: &'a [Rule]) -> Vec<(&'a Rule, &'a str)> {
+    let normalized = |rule: &Rule| -> Vec<String> {
+        rule.selectors()
+            .into_iter()
+            .map(normalize_selector)
+            .collect()
+    };
+    let selectors = normalized(rule);
+    let mut overrides = Vec::new();
+
+    for other in rules
+        .iter()
+        .filter(|r| r.selector_start > rule.selector_start && normalized(r) == selectors)
+    {
+        for decl in &other.declarations {
+            if rule.declaration(&decl.property).is_some() {
+                overrides.push((other, decl.property.as_str()));
+            }
+        }
+    }
+
+    overrides
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specificity_counts_ids_classes_and_types() {
+        assert_eq!(specificity("#root .panel > Button"), (1, 1, 1));
+        assert_eq!(specificity(".a.b:hover"), (0, 3, 0));
+        assert_eq!(specificity("*"), (0, 0, 0));
+    }
+
+    #[test]
+    fn specificity_counts_double_colon_pseudo_classes_as_classes() {
+        assert_eq!(specificity("Button::hover"), (0, 1, 1));
+        assert_eq!(specificity(".a:hover::focus"), (0, 3, 0));
+    }
+
+    #[test]
+    fn overrides_match_selectors_regardless_of_whitespace() {
+        let stylesheet = parse(".a>.b { color: red; }\n.a  >  .b { color: blue; margin: 0; }");
+        let overrides = find_overrides(&stylesheet.rules[0], &stylesheet.rules);
+
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(overrides[0].1, "color");
+        assert_eq!(
+            overrides[0].0.selector_start,
+            stylesheet.rules[1].selector_start
+        );
+    }
+
+    #[test]
+    fn overrides_ignore_different_selectors() {
+        let stylesheet = parse(".a .b { color: red; }\n.a > .b { color: blue; }");
+        assert!(find_overrides(&stylesheet.rules[0], &stylesheet.rules).is_empty());
+    }
+}
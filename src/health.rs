@@ -0,0 +1,98 @@
+//! Health status for USS Language Server
+//!
+//! Records whether each subsystem started correctly, so degraded features are
+//! reported through `uss/status` and a startup message instead of silently
+//! disappearing.
+
+use serde::Serialize;
+use std::any::Any;
+
+/// State of a subsystem
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubsystemState {
+    /// Working normally
+    #[default]
+    Ok,
+    /// Working with reduced functionality
+    Degraded,
+    /// Not working at all
+    Unavailable,
+}
+
+/// Status of one subsystem
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SubsystemStatus {
+    pub state: SubsystemState,
+    /// What went wrong and which features are affected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl SubsystemStatus {
+    pub fn ok() -> Self {
+        Self::default()
+    }
+
+    pub fn degraded(message: impl Into<String>) -> Self {
+        let mut status = Self::ok();
+        status.report(SubsystemState::Degraded, message);
+        status
+    }
+
+    pub fn unavailable(message: impl Into<String>) -> Self {
+        let mut status = Self::ok();
+        status.report(SubsystemState::Unavailable, message);
+        status
+    }
+
+    /// Record a problem, keeping the worst state and every message
+    pub fn report(&mut self, state: SubsystemState, message: impl Into<String>) {
+        self.state = self.state.max(state);
+        let message = message.into();
+        self.message = Some(match self.message.take() {
+            Some(previous) => format!("{} {}", previous, message),
+            None => message,
+        });
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.state == SubsystemState::Ok
+    }
+}
+
+/// Result of the `uss/status` request
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatus {
+    /// Parsing of `initializationOptions` and configuration changes
+    pub settings: SubsystemStatus,
+    /// Workspace index of style sheets, including preloaded files
+    pub index: SubsystemStatus,
+    /// File watcher keeping the index up to date
+    pub watcher: SubsystemStatus,
+    /// Scan of UXML documents for the classes they assign
+    pub uxml: SubsystemStatus,
+}
+
+impl ServerStatus {
+    /// One message describing every subsystem that is not working normally
+    pub fn degraded_summary(&self) -> Option<String> {
+        let messages: Vec<&str> = [&self.settings, &self.index, &self.watcher, &self.uxml]
+            .into_iter()
+            .filter_map(|status| status.message.as_deref())
+            .collect();
+        (!messages.is_empty()).then(|| format!("USS: {}", messages.join(" ")))
+    }
+}
+
+/// The message a panic was raised with
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
@@ -3,16 +3,30 @@
 //! Provides hover information for USS properties, values, selectors, and elements.
 
 use crate::document::Document;
-use crate::uss_data::{USS_COLORS, USS_PROPERTIES, USS_PSEUDO_CLASSES, USS_UNITS, UXML_ELEMENTS};
-use tower_lsp::lsp_types::*;
+use crate::tokenizer::{tokenize, TokenKind};
+use crate::uss_data::{
+    UNIVERSAL_SELECTOR_DESCRIPTION, USS_BEST_PRACTICES_URL, USS_COLORS, USS_PROPERTIES,
+    USS_PSEUDO_CLASSES, USS_UNITS, UXML_ELEMENTS,
+};
+use crate::values::{axis_name, split_components, transform_origin_axes, Axis};
+use lsp_types::*;
+use once_cell::sync::Lazy;
+use regex::Regex;
 
 /// Get hover information at a position
 pub fn get_hover(doc: &Document, position: Position) -> Option<Hover> {
-    let word = doc.get_word_at_position(position)?;
-    let line = doc.get_line(position.line)?;
-
-    // Check what context we're in
-    let hover_content = get_hover_content(&word, &line, position);
+    // The universal selector is not a word, so check it first
+    let hover_content = if is_universal_selector_at(doc, position) {
+        Some(format!(
+            "## Universal selector `*`\n\n{}\n\n[USS best practices]({})",
+            UNIVERSAL_SELECTOR_DESCRIPTION, USS_BEST_PRACTICES_URL
+        ))
+    } else {
+        let word = doc.get_word_at_position(position)?;
+        let line = doc.get_line(position.line)?;
+        let before = doc.get_text_before_cursor(position)?;
+        get_hover_content(&word, &line, &before)
+    };
 
     hover_content.map(|content| Hover {
         contents: HoverContents::Markup(MarkupContent {
@@ -23,8 +37,30 @@ pub fn get_hover(doc: &Document, position: Position) -> Option<Hover> {
     })
 }
 
+/// Check whether a position is on a `*` token outside comments
+fn is_universal_selector_at(doc: &Document, position: Position) -> bool {
+    let Some(offset) = doc.position_to_byte_offset(position) else {
+        return false;
+    };
+    let text = doc.get_text();
+    tokenize(&text)
+        .iter()
+        .any(|t| t.kind == TokenKind::Delim('*') && (t.start == offset || t.end == offset))
+}
+
+/// Regex for the start of a transform-origin value
+static TRANSFORM_ORIGIN_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"transform-origin\s*:\s*").unwrap());
+
 /// Get hover content based on the word and context
-fn get_hover_content(word: &str, line: &str, position: Position) -> Option<String> {
+///
+/// `before` is the part of `line` before the cursor.
+fn get_hover_content(word: &str, line: &str, before: &str) -> Option<String> {
+    // Check if it's a component of a transform-origin value
+    if let Some(content) = get_transform_origin_hover(line, before.len()) {
+        return Some(content);
+    }
+
     // Check if it's a USS property
     if let Some(prop) = USS_PROPERTIES.get(word) {
         return Some(format!(
@@ -46,7 +82,7 @@ fn get_hover_content(word: &str, line: &str, position: Position) -> Option<Strin
     }
 
     // Check if it's a pseudo-class
-    if word.starts_with(':') || line[..position.character as usize].ends_with(':') {
+    if word.starts_with(':') || before.ends_with(':') {
         let pseudo_name = word.trim_start_matches(':');
         if let Some(pc) = USS_PSEUDO_CLASSES.iter().find(|p| p.name == pseudo_name) {
             return Some(format!("## :{}\n\n{}", pc.name, pc.description));
@@ -203,3 +239,101 @@ fn get_hover_content(word: &str, line: &str, position: Position) -> Option<Strin
         _ => None,
     }
 }
+
+/// Explain which axis a transform-origin component under the cursor sets
+///
+/// `col` is the cursor's byte offset within `line`.
+fn get_transform_origin_hover(line: &str, col: usize) -> Option<String> {
+    let m = TRANSFORM_ORIGIN_PATTERN.find(line)?;
+    let value_end = line[m.end()..]
+        .find([';', '}'])
+        .map_or(line.len(), |i| m.end() + i);
+    let value = &line[m.end()..value_end];
+
+    let cursor = col.checked_sub(m.end())?;
+
+    let components = split_components(value);
+    let axes = transform_origin_axes(&components);
+    let (index, component) = components
+        .iter()
+        .enumerate()
+        .find(|(_, c)| c.start <= cursor && cursor <= c.end)?;
+
+    let explanation = match axes.get(index).copied().flatten()? {
+        Axis::Either => "Sets both axes to the center of the element.".to_string(),
+        axis if components.len() == 1 => format!(
+            "Sets the **{}** origin; the other axis defaults to `center`.",
+            axis_name(axis)
+        ),
+        axis => format!("Sets the **{}** origin.", axis_name(axis)),
+    };
+
+    Some(format!(
+        "## transform-origin: `{}`\n\n{}",
+        component.text, explanation
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hover text at the `|` in a text
+    fn hover(text: &str) -> Option<String> {
+        let offset = text.find('|').unwrap();
+        let doc = Document::new(text.replacen('|', "", 1), 1);
+        let position = doc.byte_offset_to_position(offset);
+        get_hover(&doc, position).map(|hover| match hover.contents {
+            HoverContents::Markup(content) => content.value,
+            contents => panic!("unexpected hover contents {:?}", contents),
+        })
+    }
+
+    #[test]
+    fn universal_selector_hover() {
+        for text in [
+            ".panel |* { }",
+            ".panel *| { }",
+            ".a, |*.item { }",
+            "é ü |*",
+        ] {
+            let content = hover(text).unwrap_or_else(|| panic!("no hover in {:?}", text));
+            assert!(
+                content.starts_with("## Universal selector `*`"),
+                "{}",
+                content
+            );
+            assert!(content.contains(UNIVERSAL_SELECTOR_DESCRIPTION));
+            assert!(content.contains(USS_BEST_PRACTICES_URL));
+        }
+    }
+
+    #[test]
+    fn universal_selector_in_comment_has_no_hover() {
+        assert_eq!(hover("/* .panel |* */\n.a { }"), None);
+    }
+
+    /// The same cursor at the end of a line and of the text, with each line ending
+    fn at_line_ends(line: &str) -> Vec<String> {
+        vec![
+            format!(".a {{ {} }}\n", line.replace('|', "|;")),
+            format!(".a {{\n    {}\n}}\n", line),
+            format!(".a {{\r\n    {}\r\n}}\r\n", line),
+            format!(".a {{\n    {}", line),
+            format!(".a {{\n    {}\n", line),
+            format!(".a {{\r\n    {}\r\n", line),
+        ]
+    }
+
+    #[test]
+    fn hover_at_line_and_text_ends() {
+        for text in at_line_ends("color|") {
+            let content = hover(&text).unwrap_or_else(|| panic!("no hover in {:?}", text));
+            assert!(content.starts_with("## color\n"), "{:?}: {}", text, content);
+        }
+        for text in at_line_ends("color: red|") {
+            let content = hover(&text).unwrap_or_else(|| panic!("no hover in {:?}", text));
+            assert!(content.contains("red"), "{:?}: {}", text, content);
+        }
+    }
+}
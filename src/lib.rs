@@ -0,0 +1,66 @@
+//! USS Language Server library
+//!
+//! Analysis of Unity Style Sheets for embedding in other tools. [`analyze`]
+//! returns diagnostics, symbols, colors, and the formatted text of a style
+//! sheet as owned, serde-serializable types; the language server binary is
+//! built on the same functions.
+//!
+//! The `server` feature (on by default) adds the language server itself. Without
+//! it the crate compiles to `wasm32-unknown-unknown`, and the `wasm` feature
+//! exports the analysis to JavaScript.
+
+pub mod analysis;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+// Server internals shared with the binary; not part of the stable API
+#[cfg(feature = "server")]
+#[doc(hidden)]
+pub mod cli;
+#[doc(hidden)]
+pub mod code_actions;
+#[doc(hidden)]
+pub mod completion;
+#[doc(hidden)]
+pub mod diagnostics;
+#[cfg(feature = "server")]
+#[doc(hidden)]
+pub mod diagnostics_queue;
+#[doc(hidden)]
+pub mod document;
+#[doc(hidden)]
+pub mod explain;
+#[cfg(feature = "server")]
+#[doc(hidden)]
+pub mod health;
+#[doc(hidden)]
+pub mod hover;
+#[doc(hidden)]
+pub mod parser;
+#[doc(hidden)]
+pub mod position_codec;
+#[cfg(feature = "server")]
+#[doc(hidden)]
+pub mod report;
+#[doc(hidden)]
+pub mod settings;
+#[doc(hidden)]
+pub mod tokenizer;
+#[doc(hidden)]
+pub mod uss_data;
+#[doc(hidden)]
+pub mod uxml;
+#[doc(hidden)]
+pub mod values;
+#[cfg(feature = "server")]
+#[doc(hidden)]
+pub mod virtual_documents;
+#[cfg(feature = "server")]
+#[doc(hidden)]
+pub mod workspace;
+
+pub use analysis::{
+    analyze, AnalysisColor, AnalysisDiagnostic, AnalysisOptions, AnalysisResult, ClassSymbol,
+    RelatedInformation, RuleSymbol, Severity, Symbols, TextPosition, TextRange, VariableSymbol,
+};
+pub use settings::Settings;
@@ -3,43 +3,502 @@
 //! A Language Server Protocol implementation for Unity Style Sheets (USS)
 //! providing completion, diagnostics, hover, and formatting support.
 
-mod completion;
-mod diagnostics;
-mod document;
-mod hover;
-mod uss_data;
-
 use dashmap::DashMap;
-use document::Document;
-use log::info;
-use tower_lsp::jsonrpc::Result;
+use log::{error, info, warn};
+use serde_json::{json, Value};
+use std::backtrace::Backtrace;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tower_lsp::jsonrpc::{Error, ErrorCode, Result};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
+use uss_language_server::diagnostics_queue::DiagnosticsQueue;
+use uss_language_server::document::Document;
+use uss_language_server::health::{panic_message, ServerStatus, SubsystemState, SubsystemStatus};
+use uss_language_server::position_codec::PositionCodec;
+use uss_language_server::report::{FindColorOptions, VariablesReportOptions};
+use uss_language_server::settings::Settings;
+use uss_language_server::virtual_documents::{
+    VirtualContent, VirtualContentParams, VirtualDocuments,
+};
+use uss_language_server::workspace::WorkspaceIndex;
+use uss_language_server::{
+    analysis, cli, code_actions, completion, document, explain, hover, report, workspace,
+};
 
 /// USS Language Server backend
 pub struct UssLanguageServer {
     /// LSP client for sending notifications
     client: Client,
     /// Open documents indexed by URI
-    documents: DashMap<String, Document>,
+    documents: Arc<DashMap<String, Document>>,
+    /// Current server settings
+    settings: Arc<RwLock<Settings>>,
+    /// Background diagnostics publication
+    diagnostics: Arc<DiagnosticsQueue>,
+    /// Style sheets of the workspace, including files that are not open
+    index: Arc<WorkspaceIndex>,
+    /// Workspace root directory
+    root: RwLock<Option<PathBuf>>,
+    /// Status of each subsystem, served through `uss/status`
+    status: Arc<RwLock<ServerStatus>>,
+    /// Generated read-only documents served through `uss/virtualContent`
+    virtual_documents: VirtualDocuments,
+    /// Position encoding negotiated during `initialize`
+    position_codec: RwLock<PositionCodec>,
 }
 
 impl UssLanguageServer {
     pub fn new(client: Client) -> Self {
+        let documents = Arc::new(DashMap::new());
+        let settings = Arc::new(RwLock::new(Settings::default()));
+        let index = Arc::new(WorkspaceIndex::new());
+        let diagnostics = Arc::new(DiagnosticsQueue::new(
+            client.clone(),
+            Arc::clone(&documents),
+            Arc::clone(&settings),
+            Arc::clone(&index),
+        ));
+
         Self {
             client,
-            documents: DashMap::new(),
+            documents,
+            settings,
+            diagnostics,
+            index,
+            root: RwLock::new(None),
+            status: Arc::new(RwLock::new(ServerStatus::default())),
+            virtual_documents: VirtualDocuments::new(),
+            position_codec: RwLock::new(PositionCodec::default()),
+        }
+    }
+
+    /// Handle the `uss/virtualContent` request
+    ///
+    /// Returns `null` for unknown URIs and for documents invalidated by an edit
+    /// to their source.
+    async fn virtual_content(
+        &self,
+        params: VirtualContentParams,
+    ) -> Result<Option<VirtualContent>> {
+        Ok(self.virtual_documents.get(&params.uri))
+    }
+
+    /// Handle the `uss/status` request, which takes no parameters
+    async fn status(&self) -> Result<ServerStatus> {
+        Ok(self.status.read().unwrap().clone())
+    }
+
+    /// Replace the settings with client-provided ones, recording malformed settings
+    fn apply_settings(&self, value: &Value) {
+        let (settings, malformed) = Settings::parse(value);
+        let status = if malformed.is_empty() {
+            SubsystemStatus::ok()
+        } else {
+            let malformed = malformed.join(", ");
+            warn!("Malformed settings: {}", malformed);
+            SubsystemStatus::degraded(format!(
+                "Settings are malformed, using the defaults for: {}",
+                malformed
+            ))
+        };
+        *self.settings.write().unwrap() = settings;
+        self.status.write().unwrap().settings = status;
+        self.diagnostics.update_concurrency();
+    }
+
+    /// Generate a style explanation for the rule at a position
+    fn explain_styles(&self, arguments: &[Value]) -> Result<Option<Value>> {
+        let uri = string_argument(arguments, 0)?;
+        let position: Position = argument(arguments, 1)?;
+
+        let Some(doc) = self.documents.get(&uri) else {
+            return Ok(None);
+        };
+        let Some(report) = explain::explain_styles(&doc, position) else {
+            return Ok(None);
+        };
+
+        let preview = self
+            .virtual_documents
+            .create("explain", &uri, "markdown", report);
+        Ok(Some(json!({ "uri": preview })))
+    }
+
+    /// Generate the workspace variables report
+    ///
+    /// Writes the report to the requested output file, or returns it as a
+    /// virtual document when no output is given. The output file must be a
+    /// relative path inside the workspace.
+    fn variables_report(&self, arguments: &[Value]) -> Result<Option<Value>> {
+        let options: VariablesReportOptions = if arguments.is_empty() {
+            VariablesReportOptions::default()
+        } else {
+            argument(arguments, 0)?
+        };
+
+        let root = self.root.read().unwrap().clone();
+        let output = options
+            .output
+            .as_deref()
+            .map(|output| {
+                workspace::workspace_path(root.as_deref(), output).ok_or_else(|| {
+                    Error::invalid_params(format!(
+                        "Output must be a relative path inside the workspace: {}",
+                        output
+                    ))
+                })
+            })
+            .transpose()?;
+        let files = workspace::workspace_files(&self.index, &self.documents);
+        let report = report::variables_report(&files, root.as_deref(), options.format);
+
+        match output {
+            Some(path) => {
+                std::fs::write(&path, report).map_err(|e| Error {
+                    code: ErrorCode::InternalError,
+                    message: format!("Cannot write {}: {}", path.display(), e).into(),
+                    data: None,
+                })?;
+                Ok(Some(json!({ "path": path })))
+            }
+            None => {
+                let preview = self.virtual_documents.create(
+                    "report",
+                    "",
+                    options.format.language_id(),
+                    report,
+                );
+                Ok(Some(json!({ "uri": preview })))
+            }
+        }
+    }
+
+    /// Rename the occurrences of a name inside a selection
+    ///
+    /// Returns the workspace edit for the client to apply, or `null` when the
+    /// selection contains no occurrence.
+    fn rename_in_selection(&self, arguments: &[Value]) -> Result<Option<Value>> {
+        let uri = string_argument(arguments, 0)?;
+        let range: Range = argument(arguments, 1)?;
+        let old_name = string_argument(arguments, 2)?;
+        let new_name = string_argument(arguments, 3)?;
+
+        let Some(doc) = self.documents.get(&uri) else {
+            return Ok(None);
+        };
+        let edit = document::rename_in_range(&doc, range, &old_name, &new_name, &uri);
+        Ok(edit.map(|edit| json!(edit)))
+    }
+
+    /// Find the usages of a color across the workspace
+    ///
+    /// Returns the locations in the shape of a references response, or a
+    /// report document grouped by color value when requested.
+    fn find_color(&self, arguments: &[Value]) -> Result<Option<Value>> {
+        let color = string_argument(arguments, 0)?;
+        let options: FindColorOptions = if arguments.len() > 1 {
+            argument(arguments, 1)?
+        } else {
+            FindColorOptions::default()
+        };
+        let target = document::parse_color(&color)
+            .ok_or_else(|| Error::invalid_params(format!("Invalid color: {}", color)))?;
+
+        let files = workspace::workspace_files(&self.index, &self.documents);
+        let matches = report::find_color(&files, target, options.tolerance);
+
+        if options.report {
+            let root = self.root.read().unwrap().clone();
+            let report =
+                report::color_usages_report(&matches, target, options.tolerance, root.as_deref());
+            let preview = self
+                .virtual_documents
+                .create("colors", "", "markdown", report);
+            return Ok(Some(json!({ "uri": preview })));
+        }
+
+        let locations: Vec<Location> = matches
+            .iter()
+            .filter_map(|found| {
+                Some(Location {
+                    uri: Url::parse(found.uri).ok()?,
+                    range: found.usage.range,
+                })
+            })
+            .collect();
+        Ok(Some(json!(locations)))
+    }
+
+    /// Generate a read-only preview of a formatted document
+    fn preview_formatted(&self, arguments: &[Value]) -> Result<Option<Value>> {
+        let uri = string_argument(arguments, 0)?;
+        let options: FormattingOptions = if arguments.len() > 1 {
+            argument(arguments, 1)?
+        } else {
+            FormattingOptions {
+                tab_size: 4,
+                insert_spaces: true,
+                ..Default::default()
+            }
+        };
+
+        let Some(doc) = self.documents.get(&uri) else {
+            return Ok(None);
+        };
+        let formatted = document::formatted_text(&doc, &options, self.settings().format);
+
+        let preview = self
+            .virtual_documents
+            .create("formatted", &uri, "uss", formatted);
+        Ok(Some(json!({ "uri": preview })))
+    }
+
+    /// Get a snapshot of the current settings
+    fn settings(&self) -> Settings {
+        self.settings.read().unwrap().clone()
+    }
+
+    /// Run the core logic of a request, converting a panic into an internal error
+    ///
+    /// The providers are synchronous, so unwinding out of one leaves no request
+    /// half-done; the user is told which request and document failed, and the
+    /// panic hook installed in `main` has already logged the backtrace to the
+    /// log file (`USS_LOG_FILE`) or stderr.
+    async fn guarded<T>(
+        &self,
+        method: &str,
+        uri: Option<&str>,
+        handler: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        let payload = match panic::catch_unwind(AssertUnwindSafe(handler)) {
+            Ok(result) => return result,
+            Err(payload) => payload,
+        };
+
+        let message = panic_message(payload.as_ref());
+        let target = uri.map(|uri| format!(" for {}", uri)).unwrap_or_default();
+        error!("{} panicked{}: {}", method, target, message);
+        self.client
+            .show_message(
+                MessageType::ERROR,
+                format!("USS: internal error in {}{}: {}", method, target, message),
+            )
+            .await;
+
+        Err(Error {
+            code: ErrorCode::InternalError,
+            message: format!("Internal error: {}", message).into(),
+            data: None,
+        })
+    }
+
+    /// Index the configured preload files, reporting the ones that cannot be read
+    ///
+    /// Missing files are shown right away, since diagnostics computed before the
+    /// startup summary already report their variables as undefined.
+    async fn preload_files(&self) {
+        let root = self.root.read().unwrap().clone();
+        let mut missing = Vec::new();
+
+        for path in self.settings().preload_files {
+            let resolved = workspace::resolve_path(root.as_deref(), &path);
+            if let Err(e) = self.index.index_file(&resolved) {
+                warn!("Failed to preload {}: {}", resolved.display(), e);
+                missing.push(path);
+            }
+        }
+
+        if !missing.is_empty() {
+            let message = format!(
+                "Could not preload {}: variables defined there are reported as undefined.",
+                missing.join(", ")
+            );
+            self.status
+                .write()
+                .unwrap()
+                .index
+                .report(SubsystemState::Degraded, message.clone());
+            self.client
+                .show_message(MessageType::WARNING, format!("USS: {}", message))
+                .await;
         }
     }
+
+    /// Register a file watcher for style sheets in the workspace
+    async fn register_file_watcher(&self) {
+        let options = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![FileSystemWatcher {
+                glob_pattern: GlobPattern::String("**/*.{uss,tss,uxml}".to_string()),
+                kind: None,
+            }],
+        };
+        let registration = Registration {
+            id: "uss-file-watcher".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(options).ok(),
+        };
+
+        if let Err(e) = self.client.register_capability(vec![registration]).await {
+            warn!("Failed to register file watcher: {}", e);
+            self.status.write().unwrap().watcher = SubsystemStatus::unavailable(format!(
+                "File watching unavailable ({}): {}",
+                e.message, WATCHER_FALLBACK
+            ));
+        }
+    }
+
+    /// Index the workspace root in the background, then refresh diagnostics
+    ///
+    /// Reports degraded subsystems to the user once the scan has finished.
+    fn spawn_workspace_scan(&self) {
+        let client = self.client.clone();
+        let status = Arc::clone(&self.status);
+        let Some(root) = self.root.read().unwrap().clone() else {
+            tokio::spawn(report_status(client, status));
+            return;
+        };
+        let index = Arc::clone(&self.index);
+        let diagnostics = Arc::clone(&self.diagnostics);
+
+        tokio::spawn(async move {
+            let scan_root = root.clone();
+            let result = tokio::task::spawn_blocking(move || index.scan(&scan_root)).await;
+            let problem = match result {
+                Ok(Ok(summary)) => {
+                    info!(
+                        "Indexed {} style sheet(s) and {} UXML file(s) in {}",
+                        summary.indexed,
+                        summary.uxml_indexed,
+                        root.display()
+                    );
+                    if summary.uxml_skipped > 0 {
+                        let message = format!(
+                            "{} UXML file(s) in the workspace could not be read: their classes are not offered in completion.",
+                            summary.uxml_skipped
+                        );
+                        warn!("{}", message);
+                        status
+                            .write()
+                            .unwrap()
+                            .uxml
+                            .report(SubsystemState::Degraded, message);
+                    }
+                    (summary.skipped > 0).then(|| {
+                        (
+                            SubsystemState::Degraded,
+                            format!(
+                                "{} file(s) or folders in the workspace could not be read and were skipped.",
+                                summary.skipped
+                            ),
+                        )
+                    })
+                }
+                Ok(Err(e)) => Some((
+                    SubsystemState::Unavailable,
+                    format!(
+                        "Cannot read the workspace folder {} ({}): cross-file features only see open documents.",
+                        root.display(),
+                        e
+                    ),
+                )),
+                Err(e) => Some((
+                    SubsystemState::Unavailable,
+                    format!(
+                        "Workspace scan failed ({}): cross-file features only see open documents.",
+                        e
+                    ),
+                )),
+            };
+            if let Some((state, message)) = problem {
+                warn!("{}", message);
+                let mut status = status.write().unwrap();
+                if state == SubsystemState::Unavailable {
+                    status.uxml = SubsystemStatus::unavailable(UXML_FALLBACK);
+                }
+                status.index.report(state, message);
+            }
+
+            diagnostics.schedule_all();
+            report_status(client, status).await;
+        });
+    }
+}
+
+/// Shown when the file watcher is unavailable
+const WATCHER_FALLBACK: &str =
+    "cross-file features refresh only when style sheets are saved in the editor.";
+
+/// Shown when the workspace cannot be scanned for UXML files
+const UXML_FALLBACK: &str =
+    "UXML files are not scanned, so classes used only in UXML are not offered in completion.";
+
+/// Show one message summarizing the subsystems that are not working normally
+async fn report_status(client: Client, status: Arc<RwLock<ServerStatus>>) {
+    let summary = status.read().unwrap().degraded_summary();
+    if let Some(summary) = summary {
+        client.show_message(MessageType::WARNING, summary).await;
+    }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for UssLanguageServer {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
         info!("USS Language Server initializing...");
 
+        if let Some(options) = params.initialization_options {
+            self.apply_settings(&options);
+        }
+
+        #[allow(deprecated)]
+        let root_uri = params
+            .workspace_folders
+            .as_ref()
+            .and_then(|folders| folders.first())
+            .map(|folder| folder.uri.clone())
+            .or(params.root_uri);
+        let root = root_uri.and_then(|uri| uri.to_file_path().ok());
+        if root.is_none() {
+            let mut status = self.status.write().unwrap();
+            status.index = SubsystemStatus::unavailable(
+                "No workspace folder is open: cross-file features only see open documents.",
+            );
+            status.uxml = SubsystemStatus::unavailable(UXML_FALLBACK);
+        }
+        *self.root.write().unwrap() = root;
+
+        let watcher_supported = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.did_change_watched_files)
+            .and_then(|watched| watched.dynamic_registration)
+            .unwrap_or(false);
+        if !watcher_supported {
+            self.status.write().unwrap().watcher = SubsystemStatus::unavailable(format!(
+                "File watching unavailable (not supported by the editor): {}",
+                WATCHER_FALLBACK
+            ));
+        }
+
+        let position_codec = PositionCodec::negotiate(
+            params
+                .capabilities
+                .general
+                .as_ref()
+                .and_then(|general| general.position_encodings.as_deref()),
+        );
+        *self.position_codec.write().unwrap() = position_codec;
+        self.index.set_codec(position_codec);
+        info!("Using {:?} position encoding", position_codec);
+
+        // Preloaded files are indexed before any document is opened
+        self.preload_files().await;
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
+                position_encoding: Some(position_codec.kind()),
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
                     TextDocumentSyncKind::INCREMENTAL,
                 )),
@@ -62,6 +521,26 @@ impl LanguageServer for UssLanguageServer {
                 references_provider: Some(OneOf::Left(true)),
                 rename_provider: Some(OneOf::Left(true)),
                 color_provider: Some(ColorProviderCapability::Simple(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![
+                            CodeActionKind::QUICKFIX,
+                            CodeActionKind::REFACTOR_EXTRACT,
+                            CodeActionKind::SOURCE,
+                        ]),
+                        ..Default::default()
+                    },
+                )),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![
+                        "uss.explainStyles".to_string(),
+                        "uss.previewFormatted".to_string(),
+                        "uss.variablesReport".to_string(),
+                        "uss.renameInSelection".to_string(),
+                        "uss.findColor".to_string(),
+                    ],
+                    ..Default::default()
+                }),
                 ..Default::default()
             },
             server_info: Some(ServerInfo {
@@ -73,6 +552,12 @@ impl LanguageServer for UssLanguageServer {
 
     async fn initialized(&self, _: InitializedParams) {
         info!("USS Language Server initialized!");
+
+        if self.status.read().unwrap().watcher.is_ok() {
+            self.register_file_watcher().await;
+        }
+        self.spawn_workspace_scan();
+
         self.client
             .log_message(MessageType::INFO, "USS Language Server ready")
             .await;
@@ -83,16 +568,45 @@ impl LanguageServer for UssLanguageServer {
         Ok(())
     }
 
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        self.apply_settings(&params.settings);
+
+        // Settings can affect diagnostics, so refresh all open documents
+        self.diagnostics.schedule_all();
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let style_sheets_changed = params
+            .changes
+            .iter()
+            .any(|change| !workspace::is_uxml_file(Path::new(change.uri.path())));
+        for change in params.changes {
+            let Ok(path) = change.uri.to_file_path() else {
+                continue;
+            };
+            if change.typ == FileChangeType::DELETED {
+                self.index.remove(change.uri.as_str());
+            } else if let Err(e) = self.index.index_file(&path) {
+                warn!("Failed to index {}: {}", path.display(), e);
+            }
+        }
+
+        // Definitions in other files can affect undefined-variable checks
+        if style_sheets_changed {
+            self.diagnostics.schedule_all();
+        }
+    }
+
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let uri = params.text_document.uri.to_string();
         let text = params.text_document.text;
         let version = params.text_document.version;
 
-        let doc = Document::new(text, version);
+        let doc = Document::new(text, version).with_codec(*self.position_codec.read().unwrap());
         self.documents.insert(uri.clone(), doc);
 
         // Publish diagnostics for the opened document
-        self.publish_diagnostics(&uri).await;
+        self.diagnostics.schedule(&uri);
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
@@ -109,14 +623,17 @@ impl LanguageServer for UssLanguageServer {
             }
             doc.version = params.text_document.version;
         }
+        self.virtual_documents.invalidate_source(&uri);
 
         // Publish diagnostics for the changed document
-        self.publish_diagnostics(&uri).await;
+        self.diagnostics.schedule_edit(&uri);
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri = params.text_document.uri.to_string();
         self.documents.remove(&uri);
+        self.virtual_documents.remove(&uri);
+        self.virtual_documents.invalidate_source(&uri);
 
         // Clear diagnostics
         self.client
@@ -126,24 +643,51 @@ impl LanguageServer for UssLanguageServer {
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
         let uri = params.text_document.uri.to_string();
-        self.publish_diagnostics(&uri).await;
+
+        // Without a file watcher, saves are the only way the index learns of changes
+        if !self.status.read().unwrap().watcher.is_ok() {
+            if let Ok(path) = params.text_document.uri.to_file_path() {
+                if let Err(e) = self.index.index_file(&path) {
+                    warn!("Failed to index {}: {}", path.display(), e);
+                }
+            }
+            self.diagnostics.schedule_all();
+        } else {
+            self.diagnostics.schedule(&uri);
+        }
     }
 
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         let uri = params.text_document_position.text_document.uri.to_string();
         let position = params.text_document_position.position;
 
-        if let Some(doc) = self.documents.get(&uri) {
-            let completions = completion::get_completions(&doc, position);
-            return Ok(Some(CompletionResponse::Array(completions)));
-        }
+        self.guarded("textDocument/completion", Some(&uri), || {
+            // Collected before locking the document to avoid re-entrant map access
+            let variables = workspace::workspace_variables(&self.index, &self.documents, &uri);
+            let uxml_classes = self.index.uxml_classes();
+
+            if let Some(doc) = self.documents.get(&uri) {
+                let completions = completion::get_completions(
+                    &doc,
+                    position,
+                    &self.settings(),
+                    &variables,
+                    &uxml_classes,
+                );
+                return Ok(Some(CompletionResponse::Array(completions)));
+            }
 
-        Ok(None)
+            Ok(None)
+        })
+        .await
     }
 
     async fn completion_resolve(&self, item: CompletionItem) -> Result<CompletionItem> {
         // Add additional documentation for resolved completions
-        Ok(completion::resolve_completion(item))
+        self.guarded("completionItem/resolve", None, || {
+            Ok(completion::resolve_completion(item))
+        })
+        .await
     }
 
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
@@ -154,22 +698,29 @@ impl LanguageServer for UssLanguageServer {
             .to_string();
         let position = params.text_document_position_params.position;
 
-        if let Some(doc) = self.documents.get(&uri) {
-            return Ok(hover::get_hover(&doc, position));
-        }
+        self.guarded("textDocument/hover", Some(&uri), || {
+            if let Some(doc) = self.documents.get(&uri) {
+                return Ok(hover::get_hover(&doc, position));
+            }
 
-        Ok(None)
+            Ok(None)
+        })
+        .await
     }
 
     async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
         let uri = params.text_document.uri.to_string();
 
-        if let Some(doc) = self.documents.get(&uri) {
-            let formatted = document::format_document(&doc, &params.options);
-            return Ok(Some(formatted));
-        }
+        self.guarded("textDocument/formatting", Some(&uri), || {
+            if let Some(doc) = self.documents.get(&uri) {
+                let formatted =
+                    document::format_document(&doc, &params.options, self.settings().format);
+                return Ok(Some(formatted));
+            }
 
-        Ok(None)
+            Ok(None)
+        })
+        .await
     }
 
     async fn range_formatting(
@@ -178,12 +729,20 @@ impl LanguageServer for UssLanguageServer {
     ) -> Result<Option<Vec<TextEdit>>> {
         let uri = params.text_document.uri.to_string();
 
-        if let Some(doc) = self.documents.get(&uri) {
-            let formatted = document::format_range(&doc, params.range, &params.options);
-            return Ok(Some(formatted));
-        }
+        self.guarded("textDocument/rangeFormatting", Some(&uri), || {
+            if let Some(doc) = self.documents.get(&uri) {
+                let formatted = document::format_range(
+                    &doc,
+                    params.range,
+                    &params.options,
+                    self.settings().format,
+                );
+                return Ok(Some(formatted));
+            }
 
-        Ok(None)
+            Ok(None)
+        })
+        .await
     }
 
     async fn goto_definition(
@@ -197,28 +756,34 @@ impl LanguageServer for UssLanguageServer {
             .to_string();
         let position = params.text_document_position_params.position;
 
-        if let Some(doc) = self.documents.get(&uri) {
-            // Find variable definition (USS custom properties)
-            if let Some(location) = document::find_definition(&doc, position, &uri) {
-                return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+        self.guarded("textDocument/definition", Some(&uri), || {
+            if let Some(doc) = self.documents.get(&uri) {
+                // Find variable definition (USS custom properties)
+                if let Some(location) = document::find_definition(&doc, position, &uri) {
+                    return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+                }
             }
-        }
 
-        Ok(None)
+            Ok(None)
+        })
+        .await
     }
 
     async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
         let uri = params.text_document_position.text_document.uri.to_string();
         let position = params.text_document_position.position;
 
-        if let Some(doc) = self.documents.get(&uri) {
-            let refs = document::find_references(&doc, position, &uri);
-            if !refs.is_empty() {
-                return Ok(Some(refs));
+        self.guarded("textDocument/references", Some(&uri), || {
+            if let Some(doc) = self.documents.get(&uri) {
+                let refs = document::find_references(&doc, position, &uri, &self.settings());
+                if !refs.is_empty() {
+                    return Ok(Some(refs));
+                }
             }
-        }
 
-        Ok(None)
+            Ok(None)
+        })
+        .await
     }
 
     async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
@@ -226,55 +791,172 @@ impl LanguageServer for UssLanguageServer {
         let position = params.text_document_position.position;
         let new_name = params.new_name;
 
-        if let Some(doc) = self.documents.get(&uri) {
-            if let Some(edit) = document::rename(&doc, position, &new_name, &uri) {
-                return Ok(Some(edit));
+        self.guarded("textDocument/rename", Some(&uri), || {
+            if let Some(doc) = self.documents.get(&uri) {
+                if let Some(edit) = document::rename(&doc, position, &new_name, &uri) {
+                    return Ok(Some(edit));
+                }
             }
-        }
 
-        Ok(None)
+            Ok(None)
+        })
+        .await
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri.to_string();
+
+        self.guarded("textDocument/codeAction", Some(&uri), || {
+            if let Some(doc) = self.documents.get(&uri) {
+                let mut actions = code_actions::get_code_actions(
+                    &doc,
+                    &params.text_document.uri,
+                    &params.context.diagnostics,
+                );
+                let wants_source = params
+                    .context
+                    .only
+                    .as_ref()
+                    .is_none_or(|only| only.contains(&CodeActionKind::SOURCE));
+                if wants_source {
+                    actions.extend(code_actions::get_source_actions(&params.text_document.uri));
+                }
+                let wants_refactor = params.context.only.as_ref().is_none_or(|only| {
+                    only.contains(&CodeActionKind::REFACTOR)
+                        || only.contains(&CodeActionKind::REFACTOR_EXTRACT)
+                });
+                if wants_refactor {
+                    actions.extend(code_actions::get_refactor_actions(
+                        &doc,
+                        &params.text_document.uri,
+                        params.range.start,
+                    ));
+                }
+                if !actions.is_empty() {
+                    return Ok(Some(actions));
+                }
+            }
+
+            Ok(None)
+        })
+        .await
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
+        // Commands taking a document have its URI as the first argument
+        let uri = params
+            .arguments
+            .first()
+            .and_then(Value::as_str)
+            .filter(|argument| Url::parse(argument).is_ok())
+            .map(str::to_string);
+
+        self.guarded(
+            "workspace/executeCommand",
+            uri.as_deref(),
+            || match params.command.as_str() {
+                "uss.explainStyles" => self.explain_styles(&params.arguments),
+                "uss.previewFormatted" => self.preview_formatted(&params.arguments),
+                "uss.variablesReport" => self.variables_report(&params.arguments),
+                "uss.renameInSelection" => self.rename_in_selection(&params.arguments),
+                "uss.findColor" => self.find_color(&params.arguments),
+                #[cfg(feature = "test-hooks")]
+                "uss.test.panic" => panic!("uss.test.panic invoked"),
+                command => Err(Error::invalid_params(format!(
+                    "Unknown command: {}",
+                    command
+                ))),
+            },
+        )
+        .await
     }
 
     async fn document_color(&self, params: DocumentColorParams) -> Result<Vec<ColorInformation>> {
         let uri = params.text_document.uri.to_string();
 
-        if let Some(doc) = self.documents.get(&uri) {
-            return Ok(document::get_colors(&doc));
-        }
+        self.guarded("textDocument/documentColor", Some(&uri), || {
+            if let Some(doc) = self.documents.get(&uri) {
+                return Ok(analysis::colors(&doc)
+                    .into_iter()
+                    .map(ColorInformation::from)
+                    .collect());
+            }
 
-        Ok(vec![])
+            Ok(vec![])
+        })
+        .await
     }
 
     async fn color_presentation(
         &self,
         params: ColorPresentationParams,
     ) -> Result<Vec<ColorPresentation>> {
+        let uri = params.text_document.uri.to_string();
         let color = params.color;
-        Ok(document::get_color_presentations(color))
+
+        self.guarded("textDocument/colorPresentation", Some(&uri), || {
+            Ok(document::get_color_presentations(color))
+        })
+        .await
     }
 }
 
-impl UssLanguageServer {
-    async fn publish_diagnostics(&self, uri: &str) {
-        if let Some(doc) = self.documents.get(uri) {
-            let diagnostics = diagnostics::get_diagnostics(&doc);
-
-            if let Ok(url) = uri.parse() {
-                self.client
-                    .publish_diagnostics(url, diagnostics, Some(doc.version))
-                    .await;
+/// Deserialize a command argument
+fn argument<T: serde::de::DeserializeOwned>(arguments: &[Value], index: usize) -> Result<T> {
+    let value = arguments
+        .get(index)
+        .ok_or_else(|| Error::invalid_params(format!("Missing argument {}", index)))?;
+    serde_json::from_value(value.clone())
+        .map_err(|e| Error::invalid_params(format!("Invalid argument {}: {}", index, e)))
+}
+
+/// Get a string command argument
+fn string_argument(arguments: &[Value], index: usize) -> Result<String> {
+    argument(arguments, index)
+}
+
+/// Environment variable naming a file to append the log to instead of stderr
+const LOG_FILE_VAR: &str = "USS_LOG_FILE";
+
+/// Initialize logging to stderr, or to the file named by `USS_LOG_FILE`
+fn init_logging() {
+    let mut builder = env_logger::Builder::from_default_env();
+    if let Some(path) = std::env::var_os(LOG_FILE_VAR).map(PathBuf::from) {
+        match std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+        {
+            Ok(file) => {
+                builder.target(env_logger::Target::Pipe(Box::new(file)));
             }
+            Err(e) => eprintln!("Cannot open log file {}: {}", path.display(), e),
         }
     }
+    builder.init();
 }
 
 #[tokio::main]
 async fn main() {
-    env_logger::init();
+    init_logging();
+
+    // Log panics with their backtrace (to the log file when configured);
+    // request handlers recover from them
+    panic::set_hook(Box::new(|info| {
+        error!("{}\n{}", info, Backtrace::force_capture());
+    }));
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
 
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, socket) = LspService::new(UssLanguageServer::new);
+    let (service, socket) = LspService::build(UssLanguageServer::new)
+        .custom_method("uss/virtualContent", UssLanguageServer::virtual_content)
+        .custom_method("uss/status", UssLanguageServer::status)
+        .finish();
     Server::new(stdin, stdout, socket).serve(service).await;
 }
@@ -0,0 +1,368 @@
+//! Parser for USS Language Server
+//!
+//! Builds a lightweight structure of rules and declarations from the token
+//! stream. All ranges are byte offsets into the source text.
+
+use crate::tokenizer::{tokenize, Token, TokenKind};
+
+/// A parsed stylesheet
+#[derive(Debug, Clone, Default)]
+pub struct Stylesheet {
+    pub rules: Vec<Rule>,
+}
+
+#[allow(dead_code)]
+/// A style rule (`selector { declarations }`)
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// The selector text, trimmed
+    pub selector: String,
+    /// Byte range of the selector text
+    pub selector_start: usize,
+    pub selector_end: usize,
+    /// Byte offset of the opening brace
+    pub block_start: usize,
+    /// Byte offset just past the closing brace (or end of text if unclosed)
+    pub block_end: usize,
+    pub declarations: Vec<Declaration>,
+    /// Malformed pseudo-class separators recovered from while parsing the selector
+    pub selector_errors: Vec<SelectorError>,
+}
+
+/// A malformed pseudo-class separator in a selector
+///
+/// The parser reads the separator as `:` and continues, so replacing the
+/// range with `:` fixes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorError {
+    pub kind: SelectorErrorKind,
+    /// Byte range of the separator
+    pub start: usize,
+    pub end: usize,
+    /// The name following the separator (`hover` in `Button::hover`)
+    pub name: String,
+}
+
+/// Kind of a malformed pseudo-class separator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorErrorKind {
+    /// `Button: hover`
+    SpaceAfterColon,
+    /// `Button::hover`; USS has no pseudo-elements
+    DoubleColon,
+    /// `Button;hover`
+    Semicolon,
+}
+
+/// A class (`.name`) or ID (`#name`) in a selector
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorName {
+    /// `.` for a class, `#` for an ID
+    pub prefix: char,
+    /// The name without its prefix
+    pub name: String,
+    /// Byte range of the name, excluding the prefix
+    pub start: usize,
+    pub end: usize,
+}
+
+#[allow(dead_code)]
+/// A declaration (`property: value;`)
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    pub property: String,
+    /// The value text, trimmed
+    pub value: String,
+    /// Byte range of the property name
+    pub property_start: usize,
+    pub property_end: usize,
+    /// Byte range of the value text
+    pub value_start: usize,
+    pub value_end: usize,
+    /// Byte offset just past the declaration, including its semicolon if present
+    pub end: usize,
+    pub has_semicolon: bool,
+}
+
+impl Stylesheet {
+    /// Find the rule whose selector or block contains a byte offset
+    pub fn rule_at(&self, offset: usize) -> Option<&Rule> {
+        self.rules
+            .iter()
+            .find(|r| r.selector_start <= offset && offset < r.block_end)
+    }
+
+    /// Classes and IDs in the selectors of all rules, in document order
+    pub fn selector_names(&self) -> Vec<SelectorName> {
+        let mut names = Vec::new();
+        for rule in &self.rules {
+            let tokens = tokenize(&rule.selector);
+            for (i, token) in tokens.iter().enumerate() {
+                let (prefix, name) = match (token.kind, tokens.get(i + 1)) {
+                    (TokenKind::Hash, _) => (
+                        '#',
+                        Token {
+                            start: token.start + 1,
+                            ..*token
+                        },
+                    ),
+                    (TokenKind::Delim('.'), Some(next))
+                        if next.kind == TokenKind::Ident && next.start == token.end =>
+                    {
+                        ('.', *next)
+                    }
+                    _ => continue,
+                };
+                names.push(SelectorName {
+                    prefix,
+                    name: name.text(&rule.selector).to_string(),
+                    start: rule.selector_start + name.start,
+                    end: rule.selector_start + name.end,
+                });
+            }
+        }
+        names
+    }
+}
+
+impl Rule {
+    /// Selectors of the rule's comma-separated selector list, trimmed
+    pub fn selectors(&self) -> Vec<&str> {
+        self.selector.split(',').map(str::trim).collect()
+    }
+
+    /// Find the first declaration of a property in this rule
+    pub fn declaration(&self, property: &str) -> Option<&Declaration> {
+        self.declarations.iter().find(|d| d.property == property)
+    }
+
+    /// The declarations as `(property, value)` pairs, ignoring whitespace and
+    /// comments between them
+    ///
+    /// Rules with equal bodies declare the same values in the same order.
+    pub fn normalized_body(&self) -> Vec<(&str, String)> {
+        self.declarations
+            .iter()
+            .map(|decl| {
+                let value = decl.value.split_whitespace().collect::<Vec<_>>().join(" ");
+                (decl.property.as_str(), value)
+            })
+            .collect()
+    }
+}
+
+/// Normalize a single selector for comparison
+///
+/// Collapses whitespace and drops it around child combinators, so
+/// `.a  >  Button` and `.a>Button` compare equal.
+pub fn normalize_selector(selector: &str) -> String {
+    let collapsed = selector.split_whitespace().collect::<Vec<_>>().join(" ");
+    collapsed.replace(" >", ">").replace("> ", ">")
+}
+
+/// Parse USS source text
+pub fn parse(text: &str) -> Stylesheet {
+    let tokens = tokenize(text);
+    let mut rules = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        // Skip leading trivia before a selector or at-rule
+        if is_trivia(&tokens[i]) {
+            i += 1;
+            continue;
+        }
+
+        // At-rules such as `@import "file.uss";`
+        if tokens[i].kind == TokenKind::Delim('@') {
+            while i < tokens.len() && !matches!(tokens[i].kind, TokenKind::Delim(';')) {
+                i += 1;
+            }
+            i += 1;
+            continue;
+        }
+
+        // Stray closing brace at the top level
+        if tokens[i].kind == TokenKind::Delim('}') {
+            i += 1;
+            continue;
+        }
+
+        let selector_tokens = i;
+        let selector_start = tokens[i].start;
+        let mut selector_end = selector_start;
+        while i < tokens.len() && tokens[i].kind != TokenKind::Delim('{') {
+            if !is_trivia(&tokens[i]) {
+                selector_end = tokens[i].end;
+            }
+            i += 1;
+        }
+        if i >= tokens.len() {
+            break;
+        }
+
+        let block_token = i;
+        let block_start = tokens[i].start;
+        i += 1;
+        let (declarations, next) = parse_block(text, &tokens, i);
+        i = next;
+
+        let block_end = if i < tokens.len() {
+            i += 1;
+            tokens[i - 1].end
+        } else {
+            text.len()
+        };
+
+        rules.push(Rule {
+            selector: text[selector_start..selector_end].to_string(),
+            selector_start,
+            selector_end,
+            block_start,
+            block_end,
+            declarations,
+            selector_errors: selector_errors(text, &tokens[selector_tokens..block_token]),
+        });
+    }
+
+    Stylesheet { rules }
+}
+
+/// Find malformed pseudo-class separators in the tokens of a selector
+fn selector_errors(text: &str, tokens: &[Token]) -> Vec<SelectorError> {
+    let mut errors = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = tokens[i];
+        let next = tokens.get(i + 1);
+        let after = tokens.get(i + 2);
+        let error = match (token.kind, next.map(|t| t.kind), after.map(|t| t.kind)) {
+            (TokenKind::Delim(':'), Some(TokenKind::Delim(':')), Some(TokenKind::Ident)) => {
+                Some((SelectorErrorKind::DoubleColon, 2))
+            }
+            (TokenKind::Delim(':'), Some(TokenKind::Whitespace), Some(TokenKind::Ident)) => {
+                Some((SelectorErrorKind::SpaceAfterColon, 2))
+            }
+            (TokenKind::Delim(';'), Some(TokenKind::Ident), _) => {
+                Some((SelectorErrorKind::Semicolon, 1))
+            }
+            _ => None,
+        };
+
+        match error {
+            Some((kind, length)) => {
+                let end = tokens[i + length - 1].end;
+                errors.push(SelectorError {
+                    kind,
+                    start: token.start,
+                    end,
+                    name: tokens[i + length].text(text).to_string(),
+                });
+                i += length + 1;
+            }
+            None => i += 1,
+        }
+    }
+
+    errors
+}
+
+/// Parse declarations up to the closing brace, returning the index of the `}` token
+fn parse_block(text: &str, tokens: &[Token], mut i: usize) -> (Vec<Declaration>, usize) {
+    let mut declarations = Vec::new();
+
+    while i < tokens.len() {
+        match tokens[i].kind {
+            TokenKind::Delim('}') => break,
+            TokenKind::Ident => {
+                let (declaration, next) = parse_declaration(text, tokens, i);
+                declarations.extend(declaration);
+                i = next;
+            }
+            _ => i += 1,
+        }
+    }
+
+    (declarations, i)
+}
+
+/// Parse a single declaration starting at a property identifier
+fn parse_declaration(text: &str, tokens: &[Token], start: usize) -> (Option<Declaration>, usize) {
+    let property = tokens[start];
+    let mut i = start + 1;
+    while i < tokens.len() && is_trivia(&tokens[i]) {
+        i += 1;
+    }
+    if i >= tokens.len() || tokens[i].kind != TokenKind::Delim(':') {
+        return (None, i);
+    }
+    i += 1;
+
+    let mut value_start = None;
+    let mut value_end = tokens[i - 1].end;
+    let mut paren_depth = 0;
+    while i < tokens.len() {
+        // A property name on a following line means the semicolon is missing
+        if paren_depth <= 0 && value_start.is_some() && starts_new_declaration(text, tokens, i) {
+            break;
+        }
+        match tokens[i].kind {
+            TokenKind::Delim('(') => paren_depth += 1,
+            TokenKind::Delim(')') => paren_depth -= 1,
+            TokenKind::Delim(';') if paren_depth <= 0 => break,
+            TokenKind::Delim('}') => break,
+            _ => {}
+        }
+        if !is_trivia(&tokens[i]) {
+            value_start.get_or_insert(tokens[i].start);
+            value_end = tokens[i].end;
+        }
+        i += 1;
+    }
+
+    let value_start = value_start.unwrap_or(value_end);
+    let has_semicolon = i < tokens.len() && tokens[i].kind == TokenKind::Delim(';');
+    let end = if has_semicolon {
+        i += 1;
+        tokens[i - 1].end
+    } else {
+        value_end
+    };
+
+    let declaration = Declaration {
+        property: property.text(text).to_string(),
+        value: text[value_start..value_end].to_string(),
+        property_start: property.start,
+        property_end: property.end,
+        value_start,
+        value_end,
+        end,
+        has_semicolon,
+    };
+
+    (Some(declaration), i)
+}
+
+/// Check whether the trivia at `i` contains a line break and is followed by `name :`
+fn starts_new_declaration(text: &str, tokens: &[Token], mut i: usize) -> bool {
+    let mut has_line_break = false;
+    while i < tokens.len() && is_trivia(&tokens[i]) {
+        has_line_break |=
+            tokens[i].kind == TokenKind::Whitespace && tokens[i].text(text).contains('\n');
+        i += 1;
+    }
+    if !has_line_break || i >= tokens.len() || tokens[i].kind != TokenKind::Ident {
+        return false;
+    }
+
+    i += 1;
+    while i < tokens.len() && is_trivia(&tokens[i]) {
+        i += 1;
+    }
+    i < tokens.len() && tokens[i].kind == TokenKind::Delim(':')
+}
+
+fn is_trivia(token: &Token) -> bool {
+    matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment)
+}
@@ -0,0 +1,80 @@
+//! Position encoding for USS Language Server
+//!
+//! LSP positions count columns in UTF-16 code units unless client and server
+//! negotiate another encoding during `initialize`. `PositionCodec` converts
+//! between wire columns and byte offsets within a line, so providers work on
+//! byte offsets and never convert columns by hand.
+
+use lsp_types::PositionEncodingKind;
+
+/// Negotiated encoding of position columns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PositionCodec {
+    /// Columns count UTF-8 bytes
+    Utf8,
+    /// Columns count UTF-16 code units (the LSP default)
+    #[default]
+    Utf16,
+}
+
+impl PositionCodec {
+    /// Choose an encoding from the client's `general.positionEncodings`
+    ///
+    /// UTF-8 is preferred because columns are then byte offsets; UTF-16 is the
+    /// fallback every client supports.
+    pub fn negotiate(offered: Option<&[PositionEncodingKind]>) -> Self {
+        match offered {
+            Some(kinds) if kinds.contains(&PositionEncodingKind::UTF8) => PositionCodec::Utf8,
+            _ => PositionCodec::Utf16,
+        }
+    }
+
+    /// The encoding kind advertised in the server capabilities
+    pub fn kind(self) -> PositionEncodingKind {
+        match self {
+            PositionCodec::Utf8 => PositionEncodingKind::UTF8,
+            PositionCodec::Utf16 => PositionEncodingKind::UTF16,
+        }
+    }
+
+    /// Byte offset within `line` of a wire column
+    ///
+    /// Columns past the end of the line clamp to its length, and columns inside
+    /// a character round down to its start.
+    pub fn byte_offset(self, line: &str, column: u32) -> usize {
+        let column = column as usize;
+        match self {
+            PositionCodec::Utf8 => {
+                let mut offset = column.min(line.len());
+                while !line.is_char_boundary(offset) {
+                    offset -= 1;
+                }
+                offset
+            }
+            PositionCodec::Utf16 => {
+                let mut units = 0;
+                for (i, c) in line.char_indices() {
+                    units += c.len_utf16();
+                    if units > column {
+                        return i;
+                    }
+                }
+                line.len()
+            }
+        }
+    }
+
+    /// Wire column of a byte offset within `line`
+    pub fn column(self, line: &str, byte_offset: usize) -> u32 {
+        let byte_offset = byte_offset.min(line.len());
+        let column = match self {
+            PositionCodec::Utf8 => byte_offset,
+            PositionCodec::Utf16 => line
+                .char_indices()
+                .take_while(|(i, _)| *i < byte_offset)
+                .map(|(_, c)| c.len_utf16())
+                .sum(),
+        };
+        column as u32
+    }
+}
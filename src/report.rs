@@ -0,0 +1,489 @@
+//! Workspace reports for USS Language Server
+//!
+//! Generates audits of the indexed style sheets, shared by the
+//! `uss.variablesReport` and `uss.findColor` commands and the `report` command
+//! line.
+
+use crate::document::{color_channels, color_hex};
+use crate::workspace::{ColorUsage, IndexedFile};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+use tower_lsp::lsp_types::{Color, Url};
+
+/// Output format of a report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Csv,
+}
+
+impl ReportFormat {
+    /// Parse a format name (`markdown`, `md`, `csv`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Some(ReportFormat::Markdown),
+            "csv" => Some(ReportFormat::Csv),
+            _ => None,
+        }
+    }
+
+    /// Language identifier of the generated text
+    pub fn language_id(self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "markdown",
+            ReportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Arguments of the `uss.variablesReport` command
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VariablesReportOptions {
+    pub format: ReportFormat,
+    /// File to write the report to, relative to the workspace root and inside
+    /// it; when omitted the report is returned as a virtual document
+    pub output: Option<String>,
+}
+
+/// Arguments of the `uss.findColor` command after the color
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FindColorOptions {
+    /// Largest difference of any channel (0-255, alpha included) for a color
+    /// to count as a near-duplicate; 0 matches equal colors only
+    pub tolerance: u8,
+    /// Return a report document grouped by color value instead of locations
+    pub report: bool,
+}
+
+/// A color found by [`find_color`]
+pub struct ColorMatch<'a> {
+    pub uri: &'a str,
+    pub usage: &'a ColorUsage,
+}
+
+/// Find the literal colors in the given files that equal `target`, or differ
+/// from it by at most `tolerance` in every channel
+pub fn find_color<'a>(
+    files: &'a [(String, IndexedFile)],
+    target: Color,
+    tolerance: u8,
+) -> Vec<ColorMatch<'a>> {
+    let target = color_channels(target);
+    files
+        .iter()
+        .flat_map(|(uri, file)| {
+            file.colors
+                .iter()
+                .map(move |usage| ColorMatch { uri, usage })
+        })
+        .filter(|found| {
+            color_channels(found.usage.color)
+                .iter()
+                .zip(target)
+                .all(|(channel, expected)| channel.abs_diff(expected) <= tolerance)
+        })
+        .collect()
+}
+
+/// Generate a Markdown report of color matches, grouped by color value
+///
+/// The searched color comes first, followed by near-duplicates from the most
+/// to the least used.
+pub fn color_usages_report(
+    matches: &[ColorMatch],
+    target: Color,
+    tolerance: u8,
+    root: Option<&Path>,
+) -> String {
+    let target = color_hex(target);
+    let mut groups: BTreeMap<String, Vec<&ColorMatch>> = BTreeMap::new();
+    for found in matches {
+        groups
+            .entry(color_hex(found.usage.color))
+            .or_default()
+            .push(found);
+    }
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by_key(|(hex, usages)| (*hex != target, std::cmp::Reverse(usages.len())));
+
+    let files: HashSet<&str> = matches.iter().map(|found| found.uri).collect();
+    let mut report = String::new();
+    let _ = writeln!(report, "# Usages of {}\n", target);
+    let _ = writeln!(
+        report,
+        "{} usages in {} files; {} distinct values within a tolerance of {}.",
+        matches.len(),
+        files.len(),
+        groups.len(),
+        tolerance
+    );
+
+    for (hex, usages) in &groups {
+        let _ = writeln!(
+            report,
+            "\n## {} ({} usage{}{})\n",
+            hex,
+            usages.len(),
+            if usages.len() == 1 { "" } else { "s" },
+            if *hex == target {
+                ""
+            } else {
+                ", near-duplicate"
+            }
+        );
+        let _ = writeln!(report, "| File | Line | Selector | Value |");
+        let _ = writeln!(report, "|------|------|----------|-------|");
+        for found in usages {
+            let _ = writeln!(
+                report,
+                "| {} | {} | `{}` | `{}` |",
+                display_path(found.uri, root),
+                found.usage.range.start.line + 1,
+                found
+                    .usage
+                    .selector
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .replace('|', "\\|"),
+                found.usage.text
+            );
+        }
+    }
+
+    report
+}
+
+/// Where a variable is defined
+struct Definition<'a> {
+    file: String,
+    line: usize,
+    value: &'a str,
+}
+
+/// Everything known about one variable
+#[derive(Default)]
+struct VariableEntry<'a> {
+    definitions: Vec<Definition<'a>>,
+    usages: usize,
+}
+
+impl VariableEntry<'_> {
+    /// Distinct values of the definitions, in order of appearance
+    fn values(&self) -> Vec<&str> {
+        let mut values: Vec<&str> = Vec::new();
+        for definition in &self.definitions {
+            if !values.contains(&definition.value) {
+                values.push(definition.value);
+            }
+        }
+        values
+    }
+
+    fn is_conflicting(&self) -> bool {
+        self.values().len() > 1
+    }
+
+    fn is_unused(&self) -> bool {
+        self.usages == 0
+    }
+}
+
+/// Generate a report of every variable defined in the given files
+///
+/// Lists each variable's values, definition sites, and usage count, flagging
+/// variables defined with different values and variables that are never used.
+/// File paths are shown relative to `root` when possible.
+pub fn variables_report(
+    files: &[(String, IndexedFile)],
+    root: Option<&Path>,
+    format: ReportFormat,
+) -> String {
+    let mut usages: HashMap<&str, usize> = HashMap::new();
+    for (_, file) in files {
+        for (name, count) in &file.usages {
+            *usages.entry(name.as_str()).or_insert(0) += count;
+        }
+    }
+
+    let mut variables: BTreeMap<&str, VariableEntry> = BTreeMap::new();
+    for (uri, file) in files {
+        for definition in &file.definitions {
+            variables
+                .entry(definition.name.as_str())
+                .or_default()
+                .definitions
+                .push(Definition {
+                    file: display_path(uri, root),
+                    line: definition.line + 1,
+                    value: &definition.value,
+                });
+        }
+    }
+    for (name, entry) in variables.iter_mut() {
+        entry.usages = usages.get(name).copied().unwrap_or(0);
+    }
+
+    match format {
+        ReportFormat::Markdown => markdown_report(&variables, files.len()),
+        ReportFormat::Csv => csv_report(&variables),
+    }
+}
+
+fn markdown_report(variables: &BTreeMap<&str, VariableEntry>, file_count: usize) -> String {
+    let conflicting = variables.values().filter(|v| v.is_conflicting()).count();
+    let unused = variables.values().filter(|v| v.is_unused()).count();
+
+    let mut report = String::new();
+    let _ = writeln!(report, "# USS Variables Report\n");
+    let _ = writeln!(
+        report,
+        "{} variables in {} files; {} with conflicting definitions, {} unused.\n",
+        variables.len(),
+        file_count,
+        conflicting,
+        unused
+    );
+    let _ = writeln!(
+        report,
+        "| Variable | Value(s) | Defined in | Usages | Notes |"
+    );
+    let _ = writeln!(
+        report,
+        "|----------|----------|------------|--------|-------|"
+    );
+
+    for (name, entry) in variables {
+        let values: Vec<String> = entry
+            .values()
+            .iter()
+            .map(|v| format!("`{}`", v.replace('|', "\\|")))
+            .collect();
+        let locations: Vec<String> = entry
+            .definitions
+            .iter()
+            .map(|d| format!("{}:{}", d.file, d.line))
+            .collect();
+        let mut notes = Vec::new();
+        if entry.is_conflicting() {
+            notes.push("conflicting definitions");
+        }
+        if entry.is_unused() {
+            notes.push("unused");
+        }
+
+        let _ = writeln!(
+            report,
+            "| `{}` | {} | {} | {} | {} |",
+            name,
+            values.join(", "),
+            locations.join(", "),
+            entry.usages,
+            notes.join(", ")
+        );
+    }
+
+    report
+}
+
+fn csv_report(variables: &BTreeMap<&str, VariableEntry>) -> String {
+    let mut report = String::from("variable,value,file,line,usages,conflicting,unused\n");
+
+    for (name, entry) in variables {
+        for definition in &entry.definitions {
+            let _ = writeln!(
+                report,
+                "{},{},{},{},{},{},{}",
+                csv_field(name),
+                csv_field(definition.value),
+                csv_field(&definition.file),
+                definition.line,
+                entry.usages,
+                entry.is_conflicting(),
+                entry.is_unused()
+            );
+        }
+    }
+
+    report
+}
+
+/// Quote a CSV field if it contains a separator, quote, or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Show a file URI as a path relative to the root, falling back to the URI
+fn display_path(uri: &str, root: Option<&Path>) -> String {
+    let Some(path) = Url::parse(uri).ok().and_then(|u| u.to_file_path().ok()) else {
+        return uri.to_string();
+    };
+    let path = root
+        .and_then(|root| path.strip_prefix(root).ok())
+        .unwrap_or(&path);
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parse_color;
+    use crate::position_codec::PositionCodec;
+
+    fn files(sources: &[(&str, &str)]) -> Vec<(String, IndexedFile)> {
+        sources
+            .iter()
+            .map(|(uri, text)| {
+                (
+                    uri.to_string(),
+                    IndexedFile::from_text(text, PositionCodec::Utf16),
+                )
+            })
+            .collect()
+    }
+
+    fn color(text: &str) -> Color {
+        parse_color(text).unwrap()
+    }
+
+    fn found(matches: &[ColorMatch]) -> Vec<String> {
+        matches
+            .iter()
+            .map(|m| format!("{}:{} {}", m.uri, m.usage.range.start.line, m.usage.text))
+            .collect()
+    }
+
+    const SHEETS: &[(&str, &str)] = &[
+        (
+            "file:///a.uss",
+            ".a { color: #FF0000; }\n.b { border-color: #fe0101; }\n",
+        ),
+        (
+            "file:///b.uss",
+            "#ff0000 { color: rgb(255, 0, 0); background-color: #00f; }\n",
+        ),
+    ];
+
+    #[test]
+    fn finds_equal_colors_in_any_notation() {
+        let files = files(SHEETS);
+        let matches = find_color(&files, color("#ff0000"), 0);
+        assert_eq!(
+            found(&matches),
+            ["file:///a.uss:0 #FF0000", "file:///b.uss:0 rgb(255, 0, 0)"]
+        );
+    }
+
+    #[test]
+    fn finds_near_duplicates_within_tolerance() {
+        let files = files(SHEETS);
+        assert_eq!(find_color(&files, color("#ff0000"), 1).len(), 3);
+        // #fe0101 differs by 1 in every channel
+        assert_eq!(
+            found(&find_color(&files, color("#fd0202"), 1)),
+            ["file:///a.uss:1 #fe0101"]
+        );
+    }
+
+    #[test]
+    fn report_groups_by_value_target_first() {
+        let files = files(SHEETS);
+        let matches = find_color(&files, color("#ff0000"), 1);
+        let report = color_usages_report(&matches, color("#ff0000"), 1, None);
+
+        assert!(report.starts_with("# Usages of #FF0000\n\n3 usages in 2 files; 2 distinct"));
+        let target = report.find("## #FF0000 (2 usages)").unwrap();
+        let near = report.find("## #FE0101 (1 usage, near-duplicate)").unwrap();
+        assert!(target < near);
+        // The hex-like ID selector is not a usage
+        assert!(report.contains("b.uss | 1 | `#ff0000` | `rgb(255, 0, 0)` |"));
+    }
+
+    const VARIABLES: &[(&str, &str)] = &[
+        (
+            "file:///work/theme.uss",
+            ":root {\n    --accent: #FF0000;\n    --font: \"Inter, sans\";\n    --unused: 4px;\n}\n",
+        ),
+        (
+            "file:///work/dark.uss",
+            ":root {\n    --accent: #00FF00;\n}\n.a { color: var(--accent); font: var(--font); }\n",
+        ),
+    ];
+
+    #[test]
+    fn markdown_report_flags_conflicting_and_unused_variables() {
+        let report = variables_report(
+            &files(VARIABLES),
+            Some(Path::new("/work")),
+            ReportFormat::Markdown,
+        );
+        assert_eq!(
+            report,
+            "# USS Variables Report\n\n\
+             3 variables in 2 files; 1 with conflicting definitions, 1 unused.\n\n\
+             | Variable | Value(s) | Defined in | Usages | Notes |\n\
+             |----------|----------|------------|--------|-------|\n\
+             | `--accent` | `#FF0000`, `#00FF00` | theme.uss:2, dark.uss:2 | 1 | conflicting definitions |\n\
+             | `--font` | `\"Inter, sans\"` | theme.uss:3 | 1 |  |\n\
+             | `--unused` | `4px` | theme.uss:4 | 0 | unused |\n"
+        );
+    }
+
+    #[test]
+    fn csv_report_has_a_row_per_definition() {
+        let report = variables_report(
+            &files(VARIABLES),
+            Some(Path::new("/work")),
+            ReportFormat::Csv,
+        );
+        assert_eq!(
+            report,
+            "variable,value,file,line,usages,conflicting,unused\n\
+             --accent,#FF0000,theme.uss,2,1,true,false\n\
+             --accent,#00FF00,dark.uss,2,1,true,false\n\
+             --font,\"\"\"Inter, sans\"\"\",theme.uss,3,1,false,false\n\
+             --unused,4px,theme.uss,4,0,false,true\n"
+        );
+    }
+
+    #[test]
+    fn csv_fields_quoted_when_needed() {
+        assert_eq!(csv_field("4px"), "4px");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+        assert_eq!(csv_field("a\rb"), "\"a\rb\"");
+    }
+
+    #[test]
+    fn same_value_in_several_files_is_not_conflicting() {
+        let files = files(&[
+            ("file:///work/a.uss", ":root { --gap: 4px; }\n"),
+            (
+                "file:///work/b.uss",
+                ":root { --gap: 4px; }\n.a { margin: var(--gap); }\n",
+            ),
+        ]);
+        let report = variables_report(&files, None, ReportFormat::Csv);
+        assert!(
+            report.contains("--gap,4px,/work/a.uss,1,1,false,false\n"),
+            "{}",
+            report
+        );
+        assert!(
+            report.contains("--gap,4px,/work/b.uss,1,1,false,false\n"),
+            "{}",
+            report
+        );
+    }
+}
@@ -0,0 +1,413 @@
+//! Settings for USS Language Server
+//!
+//! Server configuration received through `initializationOptions` and
+//! `workspace/didChangeConfiguration`.
+
+use crate::uss_data::UxmlElement;
+use lsp_types::FormattingProperty;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Server settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    /// Which UI context the stylesheets are written for
+    pub target_context: TargetContext,
+    /// Find-references settings
+    pub references: ReferencesSettings,
+    /// Diagnostics settings
+    pub diagnostics: DiagnosticsSettings,
+    /// Completion settings
+    pub completion: CompletionSettings,
+    /// Formatting settings
+    pub format: FormatSettings,
+    /// Design scales offered as value completions
+    pub scales: ScaleSettings,
+    /// Files indexed during initialization, before the workspace scan
+    pub preload_files: Vec<String>,
+}
+
+/// Diagnostics settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiagnosticsSettings {
+    /// Per-code overrides enabling or disabling individual checks
+    pub rules: HashMap<String, bool>,
+    /// Maximum number of documents whose diagnostics are computed at once
+    pub max_concurrency: usize,
+    /// When a declaration without a semicolon is reported
+    pub missing_semicolon: MissingSemicolon,
+}
+
+/// When to report declarations without a trailing semicolon
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MissingSemicolon {
+    /// Every declaration needs a semicolon
+    #[default]
+    Always,
+    /// The last declaration before `}` may omit it
+    ExceptLast,
+    /// Never report missing semicolons
+    Off,
+}
+
+impl Default for DiagnosticsSettings {
+    fn default() -> Self {
+        Self {
+            rules: HashMap::new(),
+            max_concurrency: 4,
+            missing_semicolon: MissingSemicolon::default(),
+        }
+    }
+}
+
+impl DiagnosticsSettings {
+    /// Check whether the diagnostic with the given code should be reported
+    pub fn is_enabled(&self, code: &str, default: bool) -> bool {
+        self.rules.get(code).copied().unwrap_or(default)
+    }
+}
+
+/// Completion settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CompletionSettings {
+    /// Omit properties already declared in the enclosing rule instead of
+    /// listing them last
+    pub hide_declared_properties: bool,
+}
+
+/// Design scales, in pixels
+///
+/// Empty scales add no completions.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScaleSettings {
+    /// Values for `margin` and `padding` properties
+    pub spacing: Vec<f64>,
+    /// Values for `font-size`
+    pub font_size: Vec<f64>,
+}
+
+impl ScaleSettings {
+    /// The scale used by a property, with a description of it
+    pub fn for_property(&self, property: &str) -> Option<(&[f64], &'static str)> {
+        let (scale, name) = if property.starts_with("margin") || property.starts_with("padding") {
+            (&self.spacing, "Spacing scale")
+        } else if property == "font-size" {
+            (&self.font_size, "Font size scale")
+        } else {
+            return None;
+        };
+        (!scale.is_empty()).then_some((scale.as_slice(), name))
+    }
+}
+
+/// Formatting settings
+///
+/// Each setting can be overridden for a single request through the
+/// `properties` of its `FormattingOptions`, keyed `uss.format.<name>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FormatSettings {
+    /// Where the opening brace of a rule goes
+    pub brace_style: BraceStyle,
+}
+
+/// Placement of the opening brace of a rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BraceStyle {
+    /// `.a {` on the selector line
+    #[default]
+    SameLine,
+    /// `{` on its own line after the selector
+    NextLine,
+}
+
+impl FormatSettings {
+    /// Apply the overrides of a formatting request
+    ///
+    /// Unknown keys and invalid values are ignored.
+    pub fn with_overrides(mut self, properties: &HashMap<String, FormattingProperty>) -> Self {
+        if let Some(FormattingProperty::String(style)) = properties.get("uss.format.braceStyle") {
+            if let Ok(style) = serde_json::from_value(Value::String(style.clone())) {
+                self.brace_style = style;
+            }
+        }
+        self
+    }
+}
+
+/// Find-references settings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReferencesSettings {
+    /// Also report mentions inside comments, after all code results
+    pub include_comments: bool,
+}
+
+/// UI context the stylesheets target
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetContext {
+    /// Runtime UI only; editor-only elements are not available
+    Runtime,
+    /// Editor UI only
+    Editor,
+    /// Both runtime and editor UI
+    #[default]
+    Both,
+}
+
+impl TargetContext {
+    /// Check whether an element can be used in this context
+    pub fn allows_element(self, element: &UxmlElement) -> bool {
+        match self {
+            TargetContext::Runtime => !element.editor_only,
+            TargetContext::Editor | TargetContext::Both => true,
+        }
+    }
+
+    /// Describe the UI the context targets, for diagnostic messages
+    pub fn description(self) -> &'static str {
+        match self {
+            TargetContext::Runtime => "runtime UI (targetContext: runtime)",
+            TargetContext::Editor => "editor UI (targetContext: editor)",
+            TargetContext::Both => "runtime or editor UI (targetContext: both)",
+        }
+    }
+}
+
+impl Settings {
+    /// Parse settings from a client-provided JSON value
+    ///
+    /// Accepts the settings object itself, one nested under a `uss` key, or both;
+    /// a field given at both levels takes its `uss` value. Unknown fields are
+    /// ignored; malformed fields fall back to their defaults.
+    pub fn from_value(value: &Value) -> Self {
+        Self::parse(value).0
+    }
+
+    /// Parse settings like [`Settings::from_value`], also describing each
+    /// malformed field as `path (error)`, e.g. `diagnostics.maxConcurrency (...)`
+    pub fn parse(value: &Value) -> (Self, Vec<String>) {
+        let merged;
+        let value = match (value, value.get("uss")) {
+            (Value::Object(top), Some(Value::Object(nested))) => {
+                let mut fields = top.clone();
+                fields.remove("uss");
+                fields.extend(nested.clone());
+                merged = Value::Object(fields);
+                &merged
+            }
+            (_, Some(nested)) => nested,
+            (_, None) => value,
+        };
+        if value.is_null() {
+            return (Self::default(), Vec::new());
+        }
+        let error = match serde_json::from_value(value.clone()) {
+            Ok(settings) => return (settings, Vec::new()),
+            Err(e) => e,
+        };
+        let Value::Object(fields) = value else {
+            return (Self::default(), vec![format!("uss ({})", error)]);
+        };
+
+        let mut malformed = Vec::new();
+        let valid = valid_fields(fields, &[], &mut malformed);
+        let settings = serde_json::from_value(Value::Object(valid)).unwrap_or_default();
+        (settings, malformed)
+    }
+}
+
+/// Keep the fields of a settings object that parse, recording the others
+///
+/// Nested objects are checked field by field, so one malformed entry does not
+/// discard its siblings.
+fn valid_fields(
+    fields: &Map<String, Value>,
+    path: &[&str],
+    malformed: &mut Vec<String>,
+) -> Map<String, Value> {
+    let mut valid = Map::new();
+    for (key, field) in fields {
+        let field_path: Vec<&str> = path.iter().copied().chain([key.as_str()]).collect();
+        let error = match parse_field(&field_path, field) {
+            Ok(()) => {
+                valid.insert(key.clone(), field.clone());
+                continue;
+            }
+            Err(e) => e,
+        };
+
+        if let Value::Object(nested) = field {
+            let mut nested_malformed = Vec::new();
+            let nested = Value::Object(valid_fields(nested, &field_path, &mut nested_malformed));
+            if parse_field(&field_path, &nested).is_ok() {
+                valid.insert(key.clone(), nested);
+                malformed.extend(nested_malformed);
+                continue;
+            }
+        }
+        malformed.push(format!("{} ({})", field_path.join("."), error));
+    }
+    valid
+}
+
+/// Check whether a field parses on its own, with every other setting at its default
+fn parse_field(path: &[&str], field: &Value) -> Result<(), serde_json::Error> {
+    let value = path
+        .iter()
+        .rev()
+        .fold(field.clone(), |value, key| json!({ *key: value }));
+    serde_json::from_value::<Settings>(value).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_parsed_from_uss_section() {
+        let (settings, malformed) = Settings::parse(&json!({
+            "uss": { "targetContext": "runtime", "diagnostics": { "maxConcurrency": 2 } }
+        }));
+        assert!(malformed.is_empty());
+        assert_eq!(settings.target_context, TargetContext::Runtime);
+        assert_eq!(settings.diagnostics.max_concurrency, 2);
+    }
+
+    #[test]
+    fn top_level_settings_merged_with_uss_section() {
+        let (settings, malformed) = Settings::parse(&json!({
+            "preloadFiles": ["tokens.uss"],
+            "targetContext": "editor",
+            "uss": { "targetContext": "runtime" }
+        }));
+        assert!(malformed.is_empty());
+        assert_eq!(settings.preload_files, ["tokens.uss"]);
+        assert_eq!(settings.target_context, TargetContext::Runtime);
+    }
+
+    #[test]
+    fn malformed_field_falls_back_alone() {
+        let (settings, malformed) = Settings::parse(&json!({
+            "targetContext": "runtime",
+            "diagnostics": {
+                "maxConcurrency": "many",
+                "missingSemicolon": "except-last",
+                "rules": { "unknown-property": false, "wrong-separator": "yes" }
+            },
+            "format": { "braceStyle": "next-line" },
+            "scales": 4
+        }));
+
+        assert_eq!(settings.target_context, TargetContext::Runtime);
+        assert_eq!(settings.diagnostics.max_concurrency, 4);
+        assert_eq!(
+            settings.diagnostics.missing_semicolon,
+            MissingSemicolon::ExceptLast
+        );
+        assert_eq!(
+            settings.diagnostics.rules.get("unknown-property"),
+            Some(&false)
+        );
+        assert_eq!(settings.diagnostics.rules.get("wrong-separator"), None);
+        assert_eq!(settings.format.brace_style, BraceStyle::NextLine);
+        assert!(settings.scales.spacing.is_empty());
+
+        let paths: Vec<&str> = malformed
+            .iter()
+            .map(|m| m.split(' ').next().unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "diagnostics.maxConcurrency",
+                "diagnostics.rules.wrong-separator",
+                "scales"
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_enum_object_reported_once() {
+        let (settings, malformed) = Settings::parse(&json!({
+            "targetContext": { "value": "runtime" },
+            "references": { "includeComments": true }
+        }));
+        assert_eq!(settings.target_context, TargetContext::Both);
+        assert!(settings.references.include_comments);
+        assert_eq!(malformed.len(), 1);
+        assert!(
+            malformed[0].starts_with("targetContext ("),
+            "{:?}",
+            malformed
+        );
+    }
+
+    #[test]
+    fn non_object_settings_use_the_defaults() {
+        let (settings, malformed) = Settings::parse(&json!("runtime"));
+        assert_eq!(settings.target_context, TargetContext::Both);
+        assert_eq!(malformed.len(), 1);
+        assert_eq!(Settings::parse(&Value::Null).1, Vec::<String>::new());
+    }
+
+    fn properties(entries: &[(&str, FormattingProperty)]) -> HashMap<String, FormattingProperty> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn format_overrides_apply_recognized_keys() {
+        let settings = FormatSettings::default().with_overrides(&properties(&[(
+            "uss.format.braceStyle",
+            FormattingProperty::String("next-line".to_string()),
+        )]));
+        assert_eq!(settings.brace_style, BraceStyle::NextLine);
+
+        let settings = FormatSettings {
+            brace_style: BraceStyle::NextLine,
+        }
+        .with_overrides(&properties(&[(
+            "uss.format.braceStyle",
+            FormattingProperty::String("same-line".to_string()),
+        )]));
+        assert_eq!(settings.brace_style, BraceStyle::SameLine);
+    }
+
+    #[test]
+    fn format_overrides_ignore_unknown_keys_and_invalid_values() {
+        let configured = FormatSettings {
+            brace_style: BraceStyle::NextLine,
+        };
+        for entries in [
+            vec![],
+            vec![(
+                "uss.format.braceStyle",
+                FormattingProperty::String("allman".to_string()),
+            )],
+            vec![("uss.format.braceStyle", FormattingProperty::Bool(true))],
+            vec![(
+                "braceStyle",
+                FormattingProperty::String("same-line".to_string()),
+            )],
+            vec![("insertFinalNewline", FormattingProperty::Bool(true))],
+        ] {
+            assert_eq!(
+                configured.with_overrides(&properties(&entries)),
+                configured,
+                "{:?}",
+                entries
+            );
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Tokenizer for USS Language Server
+//!
+//! Splits USS source into a flat list of tokens with byte ranges, so that
+//! features can tell code apart from comments and string contents.
+
+/// Kind of a USS token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Spaces, tabs, and line breaks
+    Whitespace,
+    /// A `/* ... */` block comment (possibly unterminated)
+    Comment,
+    /// A single- or double-quoted string (possibly unterminated)
+    String,
+    /// The unquoted contents of `url(...)` or `resource(...)`
+    Url,
+    /// An identifier such as a property name, keyword, element, or `--variable`
+    Ident,
+    /// A `#name` token (ID selector or hex color)
+    Hash,
+    /// A number with an optional unit (`10`, `1.5em`, `50%`)
+    Number,
+    /// Any other single character (`{`, `}`, `:`, `;`, `,`, `.`, `(`, `)`, ...)
+    Delim(char),
+}
+
+/// A token with its byte range in the source text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Token {
+    /// Get the source text of the token
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.start..self.end]
+    }
+
+    /// Check whether the token is a comment
+    pub fn is_comment(&self) -> bool {
+        self.kind == TokenKind::Comment
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '-' || !c.is_ascii()
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || !c.is_ascii()
+}
+
+/// Tokenize USS source text
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let kind = match c {
+            c if c.is_whitespace() => {
+                while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+                TokenKind::Whitespace
+            }
+            '/' if text[start + 1..].starts_with('*') => {
+                chars.next();
+                let mut prev = '\0';
+                for (_, c) in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+                TokenKind::Comment
+            }
+            '"' | '\'' => {
+                let quote = c;
+                let mut escaped = false;
+                while let Some((_, c)) = chars.next_if(|(_, c)| *c != '\n') {
+                    if escaped {
+                        escaped = false;
+                    } else if c == '\\' {
+                        escaped = true;
+                    } else if c == quote {
+                        break;
+                    }
+                }
+                TokenKind::String
+            }
+            '#' if chars.peek().is_some_and(|(_, c)| is_ident_char(*c)) => {
+                while chars.next_if(|(_, c)| is_ident_char(*c)).is_some() {}
+                TokenKind::Hash
+            }
+            c if c.is_ascii_digit()
+                || (matches!(c, '.' | '-' | '+')
+                    && chars.peek().is_some_and(|(_, c)| c.is_ascii_digit())) =>
+            {
+                while chars
+                    .next_if(|(_, c)| c.is_ascii_digit() || *c == '.')
+                    .is_some()
+                {}
+                if chars.next_if(|(_, c)| *c == '%').is_none() {
+                    while chars.next_if(|(_, c)| c.is_alphabetic()).is_some() {}
+                }
+                TokenKind::Number
+            }
+            c if is_ident_start(c) => {
+                while chars.next_if(|(_, c)| is_ident_char(*c)).is_some() {}
+                TokenKind::Ident
+            }
+            c => TokenKind::Delim(c),
+        };
+
+        let end = chars.peek().map(|(i, _)| *i).unwrap_or(text.len());
+        tokens.push(Token { kind, start, end });
+
+        // The contents of an unquoted url()/resource() are a single token
+        if kind == TokenKind::Delim('(') && is_url_function(text, &tokens) {
+            let rest = &text[end..];
+            let leading_ws = rest.len() - rest.trim_start().len();
+            let body = &rest[leading_ws..];
+            if !body.starts_with('"') && !body.starts_with('\'') && !body.starts_with(')') {
+                let body_len = body.find([')', '\n']).unwrap_or(body.len());
+                if leading_ws > 0 {
+                    tokens.push(Token {
+                        kind: TokenKind::Whitespace,
+                        start: end,
+                        end: end + leading_ws,
+                    });
+                }
+                let url_start = end + leading_ws;
+                tokens.push(Token {
+                    kind: TokenKind::Url,
+                    start: url_start,
+                    end: url_start + body_len,
+                });
+                while chars.next_if(|(i, _)| *i < url_start + body_len).is_some() {}
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Check whether the last token is the `(` of a `url(` or `resource(` call
+fn is_url_function(text: &str, tokens: &[Token]) -> bool {
+    match tokens.len().checked_sub(2).map(|i| tokens[i]) {
+        Some(prev) if prev.kind == TokenKind::Ident => {
+            let name = prev.text(text);
+            name.eq_ignore_ascii_case("url") || name.eq_ignore_ascii_case("resource")
+        }
+        _ => false,
+    }
+}
+
+/// Net brace depth change (`{` minus `}`) across tokens
+///
+/// Braces inside comments, strings, and unquoted urls are not counted.
+pub fn brace_delta(tokens: &[Token]) -> i32 {
+    tokens
+        .iter()
+        .map(|t| match t.kind {
+            TokenKind::Delim('{') => 1,
+            TokenKind::Delim('}') => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Net brace depth change for each line of the text
+pub fn line_brace_deltas(text: &str, tokens: &[Token]) -> Vec<i32> {
+    let newlines: Vec<usize> = text.match_indices('\n').map(|(i, _)| i).collect();
+    let mut deltas = vec![0; newlines.len() + 1];
+
+    for token in tokens {
+        let delta = brace_delta(std::slice::from_ref(token));
+        if delta != 0 {
+            deltas[newlines.partition_point(|&nl| nl < token.start)] += delta;
+        }
+    }
+
+    deltas
+}
@@ -3,6 +3,9 @@
 //! This module contains comprehensive data about USS properties, valid values,
 //! Unity UXML elements, pseudo-classes, and related documentation.
 
+use crate::values::{
+    split_components, validate_separators, validate_transform_origin, TIMING_FUNCTIONS,
+};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
@@ -14,7 +17,12 @@ pub struct UssProperty {
     pub syntax: &'static str,
     pub initial: &'static str,
     pub inherited: bool,
+    /// Only affects the text an element displays itself
+    pub applies_to_text_only: bool,
     pub values: Vec<&'static str>,
+    /// Categories of `values` in display order, each with its keywords;
+    /// completion groups keywords by category
+    pub value_groups: &'static [(&'static str, &'static [&'static str])],
 }
 
 /// Unity UXML element definition
@@ -23,6 +31,8 @@ pub struct UxmlElement {
     pub name: &'static str,
     pub description: &'static str,
     pub namespace: &'static str,
+    /// Only available in editor UI (`UnityEditor.UIElements`), not in runtime UI
+    pub editor_only: bool,
 }
 
 /// USS pseudo-class definition
@@ -32,6 +42,23 @@ pub struct UssPseudoClass {
     pub description: &'static str,
 }
 
+/// Elements that display no text of their own
+pub static NON_TEXT_ELEMENTS: &[&str] = &[
+    "VisualElement",
+    "BindableElement",
+    "Box",
+    "Image",
+    "IMGUIContainer",
+    "ScrollView",
+    "ListView",
+    "TreeView",
+    "MultiColumnListView",
+    "MultiColumnTreeView",
+    "TwoPaneSplitView",
+    "Scroller",
+    "TemplateContainer",
+];
+
 /// All USS properties
 pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(|| {
     let mut map = HashMap::new();
@@ -45,7 +72,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "row | row-reverse | column | column-reverse",
             initial: "column",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["row", "row-reverse", "column", "column-reverse"],
+            value_groups: &[],
         },
     );
 
@@ -57,7 +86,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "nowrap | wrap | wrap-reverse",
             initial: "nowrap",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["nowrap", "wrap", "wrap-reverse"],
+            value_groups: &[],
         },
     );
 
@@ -69,7 +100,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<number>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -81,7 +114,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<number>",
             initial: "1",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -93,7 +128,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "auto",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -105,7 +142,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "auto | flex-start | center | flex-end | stretch",
             initial: "stretch",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto", "flex-start", "center", "flex-end", "stretch"],
+            value_groups: &[],
         },
     );
 
@@ -117,17 +156,21 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "auto | flex-start | center | flex-end | stretch",
             initial: "auto",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto", "flex-start", "center", "flex-end", "stretch"],
+            value_groups: &[],
         },
     );
 
     map.insert("align-content", UssProperty {
         name: "align-content",
         description: "Aligns flex lines within the flex container when there is extra space on the cross axis.",
-        syntax: "auto | flex-start | center | flex-end | stretch",
+        syntax: "auto | flex-start | center | flex-end | stretch | space-between | space-around",
         initial: "auto",
         inherited: false,
+        applies_to_text_only: false,
         values: vec!["auto", "flex-start", "center", "flex-end", "stretch", "space-between", "space-around"],
+        value_groups: &[],
     });
 
     map.insert(
@@ -138,6 +181,7 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "flex-start | center | flex-end | space-between | space-around",
             initial: "flex-start",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![
                 "flex-start",
                 "center",
@@ -145,6 +189,7 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
                 "space-between",
                 "space-around",
             ],
+            value_groups: &[],
         },
     );
 
@@ -157,7 +202,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "auto",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -169,7 +216,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "auto",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -181,7 +230,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "auto",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -193,7 +244,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "auto",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -205,7 +258,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | none",
             initial: "none",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["none"],
+            value_groups: &[],
         },
     );
 
@@ -217,7 +272,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | none",
             initial: "none",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["none"],
+            value_groups: &[],
         },
     );
 
@@ -230,7 +287,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -242,7 +301,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -254,7 +315,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -266,7 +329,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -278,7 +343,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -291,7 +358,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -303,7 +372,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -315,7 +386,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -327,7 +400,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -339,7 +414,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -352,7 +429,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -364,7 +443,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -376,7 +457,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -388,7 +471,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -400,7 +485,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -412,7 +499,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<color>",
             initial: "black",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -424,7 +513,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<color>",
             initial: "black",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -436,7 +527,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<color>",
             initial: "black",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -448,7 +541,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<color>",
             initial: "black",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -460,7 +555,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<color>",
             initial: "black",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -472,7 +569,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -484,7 +583,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -496,7 +597,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -508,7 +611,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -520,7 +625,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -533,7 +640,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "relative | absolute",
             initial: "relative",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["relative", "absolute"],
+            value_groups: &[],
         },
     );
 
@@ -545,7 +654,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "auto",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -557,7 +668,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "auto",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -569,7 +682,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "auto",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -581,7 +696,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <percentage> | auto",
             initial: "auto",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["auto"],
+            value_groups: &[],
         },
     );
 
@@ -594,7 +711,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<color>",
             initial: "black",
             inherited: true,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -606,7 +725,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "12px",
             inherited: true,
+            applies_to_text_only: true,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -615,10 +736,12 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
         UssProperty {
             name: "-unity-font",
             description: "Sets the font asset (legacy).",
-            syntax: "resource(<path>) | url(<path>)",
+            syntax: "resource(<path>) | url(<path>) | none",
             initial: "none",
             inherited: true,
+            applies_to_text_only: true,
             values: vec!["none"],
+            value_groups: &[],
         },
     );
 
@@ -627,10 +750,12 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
         UssProperty {
             name: "-unity-font-definition",
             description: "Sets the font asset.",
-            syntax: "resource(<path>) | url(<path>)",
+            syntax: "resource(<path>) | url(<path>) | none",
             initial: "none",
             inherited: true,
+            applies_to_text_only: true,
             values: vec!["none"],
+            value_groups: &[],
         },
     );
 
@@ -642,7 +767,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "normal | bold | italic | bold-and-italic",
             initial: "normal",
             inherited: true,
+            applies_to_text_only: true,
             values: vec!["normal", "bold", "italic", "bold-and-italic"],
+            value_groups: &[],
         },
     );
 
@@ -652,7 +779,13 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
         syntax: "upper-left | middle-left | lower-left | upper-center | middle-center | lower-center | upper-right | middle-right | lower-right",
         initial: "upper-left",
         inherited: true,
+        applies_to_text_only: true,
         values: vec!["upper-left", "middle-left", "lower-left", "upper-center", "middle-center", "lower-center", "upper-right", "middle-right", "lower-right"],
+        value_groups: &[
+            ("upper", &["upper-left", "upper-center", "upper-right"]),
+            ("middle", &["middle-left", "middle-center", "middle-right"]),
+            ("lower", &["lower-left", "lower-center", "lower-right"]),
+        ],
     });
 
     map.insert(
@@ -663,7 +796,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: true,
+            applies_to_text_only: true,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -675,7 +810,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<color>",
             initial: "black",
             inherited: true,
+            applies_to_text_only: true,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -687,7 +824,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "normal | nowrap | pre | pre-wrap",
             initial: "normal",
             inherited: true,
+            applies_to_text_only: true,
             values: vec!["normal", "nowrap", "pre", "pre-wrap"],
+            value_groups: &[],
         },
     );
 
@@ -699,7 +838,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "clip | ellipsis",
             initial: "clip",
             inherited: false,
+            applies_to_text_only: true,
             values: vec!["clip", "ellipsis"],
+            value_groups: &[],
         },
     );
 
@@ -711,7 +852,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: true,
+            applies_to_text_only: true,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -723,7 +866,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: true,
+            applies_to_text_only: true,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -735,7 +880,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length>",
             initial: "0",
             inherited: true,
+            applies_to_text_only: true,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -748,7 +895,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<color>",
             initial: "transparent",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["transparent"],
+            value_groups: &[],
         },
     );
 
@@ -760,7 +909,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "resource(<path>) | url(<path>) | none",
             initial: "none",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["none"],
+            value_groups: &[],
         },
     );
 
@@ -772,7 +923,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "stretch-to-fill | scale-and-crop | scale-to-fit",
             initial: "stretch-to-fill",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["stretch-to-fill", "scale-and-crop", "scale-to-fit"],
+            value_groups: &[],
         },
     );
 
@@ -784,7 +937,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<color>",
             initial: "white",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -796,7 +951,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<integer>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -808,7 +965,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<integer>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -820,7 +979,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<integer>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -832,7 +993,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<integer>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -844,7 +1007,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<number>",
             initial: "1",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -857,7 +1022,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<number>",
             initial: "1",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -869,7 +1036,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "visible | hidden",
             initial: "visible",
             inherited: true,
+            applies_to_text_only: false,
             values: vec!["visible", "hidden"],
+            value_groups: &[],
         },
     );
 
@@ -881,7 +1050,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "flex | none",
             initial: "flex",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["flex", "none"],
+            value_groups: &[],
         },
     );
 
@@ -893,7 +1064,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "visible | hidden | scroll",
             initial: "visible",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["visible", "hidden", "scroll"],
+            value_groups: &[],
         },
     );
 
@@ -906,7 +1079,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<angle>",
             initial: "0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -918,7 +1093,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<number> | <number> <number> | <number> <number> <number>",
             initial: "1 1 1",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -930,7 +1107,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<length> | <length> <length> | <length> <length> <length>",
             initial: "0 0 0",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -939,14 +1118,30 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
         UssProperty {
             name: "transform-origin",
             description: "Sets the origin for transformations.",
-            syntax: "<length> | <percentage> | left | center | right | top | bottom",
+            syntax: "[ <length> | <percentage> | left | center | right ] [ <length> | <percentage> | top | center | bottom ]?",
             initial: "center",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["left", "center", "right", "top", "bottom"],
+            value_groups: &[],
         },
     );
 
     // === Transition Properties ===
+    map.insert(
+        "transition",
+        UssProperty {
+            name: "transition",
+            description: "Shorthand for transition-property, transition-duration, transition-timing-function, and transition-delay. Separate multiple transitions with commas.",
+            syntax: "[ [ all | none | <property-name> ] || <time> || <easing-function> || <time> ]#",
+            initial: "all 0s ease 0s",
+            inherited: false,
+            applies_to_text_only: false,
+            values: vec!["all", "none"],
+            value_groups: &[],
+        },
+    );
+
     map.insert(
         "transition-property",
         UssProperty {
@@ -955,7 +1150,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<property-name> | all | none",
             initial: "all",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["all", "none"],
+            value_groups: &[],
         },
     );
 
@@ -967,7 +1164,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<time>",
             initial: "0s",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -979,7 +1178,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "ease | linear | ease-in | ease-out | ease-in-out",
             initial: "ease",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["ease", "linear", "ease-in", "ease-out", "ease-in-out"],
+            value_groups: &[],
         },
     );
 
@@ -991,7 +1192,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "<time>",
             initial: "0s",
             inherited: false,
+            applies_to_text_only: false,
             values: vec![],
+            value_groups: &[],
         },
     );
 
@@ -1001,9 +1204,10 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
         UssProperty {
             name: "cursor",
             description: "Sets the cursor type.",
-            syntax: "resource(<path>) | url(<path>) | <cursor-type>",
+            syntax: "resource(<path>) | url(<path>) | arrow | text | resize-vertical | resize-horizontal | link | slide-arrow | resize-up-right | resize-up-left | move-arrow | rotate-arrow | scale-arrow | arrow-plus | arrow-minus | pan | orbit | zoom | fps | split-resize-up-down | split-resize-left-right",
             initial: "arrow",
             inherited: true,
+            applies_to_text_only: false,
             values: vec![
                 "arrow",
                 "text",
@@ -1025,6 +1229,25 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
                 "split-resize-up-down",
                 "split-resize-left-right",
             ],
+            value_groups: &[
+                (
+                    "editing",
+                    &["arrow", "text", "arrow-plus", "arrow-minus", "slide-arrow"],
+                ),
+                (
+                    "resize",
+                    &[
+                        "resize-vertical",
+                        "resize-horizontal",
+                        "resize-up-right",
+                        "resize-up-left",
+                        "split-resize-up-down",
+                        "split-resize-left-right",
+                    ],
+                ),
+                ("navigation", &["link", "pan", "orbit", "zoom", "fps"]),
+                ("tools", &["move-arrow", "rotate-arrow", "scale-arrow"]),
+            ],
         },
     );
 
@@ -1037,7 +1260,9 @@ pub static USS_PROPERTIES: Lazy<HashMap<&'static str, UssProperty>> = Lazy::new(
             syntax: "padding-box | content-box",
             initial: "padding-box",
             inherited: false,
+            applies_to_text_only: false,
             values: vec!["padding-box", "content-box"],
+            value_groups: &[],
         },
     );
 
@@ -1052,285 +1277,350 @@ pub static UXML_ELEMENTS: Lazy<Vec<UxmlElement>> = Lazy::new(|| {
             name: "VisualElement",
             description: "The base class for all visual elements.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "BindableElement",
             description: "A visual element that can be bound to a property.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Box",
             description: "A container for grouping elements.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "TextElement",
             description: "The base class for text elements.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Label",
             description: "A text label.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Image",
             description: "Displays an image.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "IMGUIContainer",
             description: "A container for IMGUI content.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Foldout",
             description: "A collapsible container.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         // Containers
         UxmlElement {
             name: "ScrollView",
             description: "A scrollable container.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "ListView",
             description: "A virtualized list view.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "TreeView",
             description: "A tree view for hierarchical data.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "MultiColumnListView",
             description: "A multi-column list view.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "MultiColumnTreeView",
             description: "A multi-column tree view.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "GroupBox",
             description: "A container with a title.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "TwoPaneSplitView",
             description: "A split view with two panes.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         // Controls
         UxmlElement {
             name: "Button",
             description: "A clickable button.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "RepeatButton",
             description: "A button that repeats its action.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Toggle",
             description: "A checkbox toggle.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Scroller",
             description: "A scrollbar control.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Slider",
             description: "A slider for float values.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "SliderInt",
             description: "A slider for integer values.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "MinMaxSlider",
             description: "A slider for selecting a range.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "ProgressBar",
             description: "A progress bar.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "DropdownField",
             description: "A dropdown selection field.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "EnumField",
             description: "A dropdown for enum values.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "EnumFlagsField",
             description: "A field for enum flags.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "RadioButton",
             description: "A radio button.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "RadioButtonGroup",
             description: "A group of radio buttons.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         // Text input
         UxmlElement {
             name: "TextField",
             description: "A text input field.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "IntegerField",
             description: "An input field for integers.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "LongField",
             description: "An input field for long integers.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "FloatField",
             description: "An input field for floats.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "DoubleField",
             description: "An input field for doubles.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Vector2Field",
             description: "An input field for Vector2.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Vector3Field",
             description: "An input field for Vector3.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Vector4Field",
             description: "An input field for Vector4.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Vector2IntField",
             description: "An input field for Vector2Int.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Vector3IntField",
             description: "An input field for Vector3Int.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "RectField",
             description: "An input field for Rect.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "RectIntField",
             description: "An input field for RectInt.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "BoundsField",
             description: "An input field for Bounds.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "BoundsIntField",
             description: "An input field for BoundsInt.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Hash128Field",
             description: "An input field for Hash128.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         // Complex fields (Editor only)
         UxmlElement {
             name: "ColorField",
             description: "A color picker field.",
             namespace: "UnityEditor.UIElements",
+            editor_only: true,
         },
         UxmlElement {
             name: "CurveField",
             description: "An animation curve field.",
             namespace: "UnityEditor.UIElements",
+            editor_only: true,
         },
         UxmlElement {
             name: "GradientField",
             description: "A gradient field.",
             namespace: "UnityEditor.UIElements",
+            editor_only: true,
         },
         UxmlElement {
             name: "ObjectField",
             description: "A field for Unity objects.",
             namespace: "UnityEditor.UIElements",
+            editor_only: true,
         },
         UxmlElement {
             name: "PropertyField",
             description: "A field for serialized properties.",
             namespace: "UnityEditor.UIElements",
+            editor_only: true,
         },
         UxmlElement {
             name: "LayerField",
             description: "A layer selection field.",
             namespace: "UnityEditor.UIElements",
+            editor_only: true,
         },
         UxmlElement {
             name: "LayerMaskField",
             description: "A layer mask field.",
             namespace: "UnityEditor.UIElements",
+            editor_only: true,
         },
         UxmlElement {
             name: "MaskField",
             description: "A mask field.",
             namespace: "UnityEditor.UIElements",
+            editor_only: true,
         },
         UxmlElement {
             name: "TagField",
             description: "A tag selection field.",
             namespace: "UnityEditor.UIElements",
+            editor_only: true,
         },
         // Templates
         UxmlElement {
             name: "Template",
             description: "A UXML template reference.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "TemplateContainer",
             description: "A container for template instances.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
         UxmlElement {
             name: "Instance",
             description: "An instance of a template.",
             namespace: "UnityEngine.UIElements",
+            editor_only: false,
         },
     ]
 });
 
+/// Unity's USS best practices page
+pub const USS_BEST_PRACTICES_URL: &str =
+    "https://docs.unity3d.com/Manual/UIE-USS-WritingStyleSheets.html";
+
+/// Description of the universal selector (`*`)
+pub const UNIVERSAL_SELECTOR_DESCRIPTION: &str = "Matches any element. \
+UI Toolkit tests every element in the hierarchy against a universal selector, so it is \
+expensive to match, especially as the key (rightmost) selector of a complex selector such \
+as `.panel *`. Prefer a class selector on the elements you want to style.";
+
 /// USS pseudo-classes
 pub static USS_PSEUDO_CLASSES: Lazy<Vec<UssPseudoClass>> = Lazy::new(|| {
     vec![
@@ -1434,3 +1724,181 @@ pub fn get_element_names() -> Vec<&'static str> {
 pub fn get_pseudo_class_names() -> Vec<&'static str> {
     USS_PSEUDO_CLASSES.iter().map(|p| p.name).collect()
 }
+
+/// Check the structural invariants of `USS_PROPERTIES`
+///
+/// Returns one message per violation, sorted by property:
+///
+/// - a keyword-only `syntax` (`visible | hidden`) lists exactly the `values`
+/// - every `values` entry appears in `syntax`, and every `value_groups`
+///   keyword in `values`
+/// - `initial` is a valid value of the property
+/// - every entry is keyed by its name, and no two names differ only by case
+///
+/// ```
+/// let problems = uss_language_server::uss_data::property_table_problems();
+/// assert!(problems.is_empty(), "{:#?}", problems);
+/// ```
+pub fn property_table_problems() -> Vec<String> {
+    let mut names: Vec<&&str> = USS_PROPERTIES.keys().collect();
+    names.sort();
+
+    let mut problems = Vec::new();
+    let mut lowercase_names = HashMap::new();
+    for name in names {
+        let prop = &USS_PROPERTIES[*name];
+        let mut problem = |message: String| problems.push(format!("{}: {}", name, message));
+
+        if prop.name != *name {
+            problem(format!("keyed by a different name ('{}')", prop.name));
+        }
+        if let Some(other) = lowercase_names.insert(name.to_ascii_lowercase(), name) {
+            problem(format!("differs from '{}' only by case", other));
+        }
+
+        for message in property_problems(prop) {
+            problem(message);
+        }
+    }
+
+    problems
+}
+
+/// Check the values, syntax, and initial value of one property
+fn property_problems(prop: &UssProperty) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for value in &prop.values {
+        if !syntax_accepts(prop.syntax, value) {
+            problems.push(format!("value '{}' is missing from the syntax", value));
+        }
+    }
+    for (group, keywords) in prop.value_groups {
+        for keyword in keywords.iter().filter(|k| !prop.values.contains(k)) {
+            problems.push(format!(
+                "'{}' keyword '{}' is not one of the values",
+                group, keyword
+            ));
+        }
+    }
+
+    if let Some(keywords) = keyword_only_syntax(prop.syntax) {
+        for keyword in keywords.iter().filter(|k| !prop.values.contains(k)) {
+            problems.push(format!(
+                "syntax keyword '{}' is missing from the values",
+                keyword
+            ));
+        }
+    }
+    problems.extend(initial_value_problems(prop));
+
+    problems
+}
+
+/// Check whether a keyword is written out in a syntax, or is a color name or
+/// timing function where the syntax takes a `<color>` or `<easing-function>`
+fn syntax_accepts(syntax: &str, keyword: &str) -> bool {
+    syntax
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .any(|word| word == keyword)
+        || (syntax.contains("<color>") && USS_COLORS.iter().any(|(name, _)| *name == keyword))
+        || (syntax.contains("<easing-function>") && TIMING_FUNCTIONS.contains(&keyword))
+}
+
+/// Keywords of a syntax that consists only of alternative keywords
+fn keyword_only_syntax(syntax: &str) -> Option<Vec<&str>> {
+    syntax
+        .split('|')
+        .map(str::trim)
+        .map(|part| {
+            let is_keyword = part.starts_with(|c: char| c.is_ascii_alphabetic())
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+            is_keyword.then_some(part)
+        })
+        .collect()
+}
+
+/// Reasons the initial value of a property is not one of its valid values
+fn initial_value_problems(prop: &UssProperty) -> Vec<String> {
+    let mut problems: Vec<String> = validate_separators(prop.name, prop.initial)
+        .iter()
+        .map(|error| {
+            format!(
+                "initial value '{}' is separated by {}, not {}",
+                prop.initial,
+                error.found.plural(),
+                error.expected.plural()
+            )
+        })
+        .collect();
+    if prop.name == "transform-origin" {
+        problems.extend(
+            validate_transform_origin(prop.initial)
+                .into_iter()
+                .map(|error| format!("initial value '{}': {}", prop.initial, error.message)),
+        );
+    }
+
+    let keyword_only = keyword_only_syntax(prop.syntax).is_some();
+    for component in split_components(prop.initial) {
+        let keyword = component.text.trim_end_matches(',');
+        let is_keyword = keyword.starts_with(|c: char| c.is_ascii_alphabetic())
+            && keyword
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if (keyword_only || is_keyword)
+            && !prop.values.contains(&keyword)
+            && !(is_keyword && syntax_accepts(prop.syntax, keyword))
+        {
+            problems.push(format!(
+                "initial value '{}' is not one of the values",
+                keyword
+            ));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn property_table_is_consistent() {
+        let mut names: Vec<&&str> = USS_PROPERTIES.keys().collect();
+        names.sort();
+        for name in names {
+            let problems = property_problems(&USS_PROPERTIES[*name]);
+            assert!(problems.is_empty(), "{}: {:#?}", name, problems);
+        }
+
+        let problems = property_table_problems();
+        assert!(problems.is_empty(), "{:#?}", problems);
+    }
+
+    #[test]
+    fn property_problems_are_detected() {
+        let prop = UssProperty {
+            name: "-test-visibility",
+            description: "",
+            syntax: "visible | hidden",
+            initial: "collapse",
+            inherited: false,
+            applies_to_text_only: false,
+            values: vec!["visible", "scroll"],
+            value_groups: &[("Shown", &["visible", "auto"])],
+        };
+
+        let problems = property_problems(&prop);
+        assert!(problems.contains(&"value 'scroll' is missing from the syntax".to_string()));
+        assert!(problems.contains(&"'Shown' keyword 'auto' is not one of the values".to_string()));
+        assert!(
+            problems.contains(&"syntax keyword 'hidden' is missing from the values".to_string())
+        );
+        assert!(
+            problems.iter().any(|p| p.contains("collapse")),
+            "{:#?}",
+            problems
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! UXML class extraction for USS Language Server
+//!
+//! UXML documents assign classes to elements with `class="a b"` attributes.
+//! The workspace scan collects them, so class completion can offer classes that
+//! are so far only used in UXML.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashSet;
+
+/// A `class` attribute with its double- or single-quoted value
+static CLASS_ATTRIBUTE_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?:^|\s)class\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+/// An XML comment
+static COMMENT_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+
+/// A name usable as a USS class selector
+static CLASS_NAME_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^-?[A-Za-z_][\w-]*$").unwrap());
+
+/// Names of the classes assigned in a UXML document, outside comments
+pub fn class_names(text: &str) -> HashSet<String> {
+    let text = COMMENT_PATTERN.replace_all(text, "");
+    CLASS_ATTRIBUTE_PATTERN
+        .captures_iter(&text)
+        .filter_map(|caps| caps.get(1).or(caps.get(2)))
+        .flat_map(|value| value.as_str().split_whitespace())
+        .filter(|name| CLASS_NAME_PATTERN.is_match(name))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: HashSet<String>) -> Vec<String> {
+        let mut names: Vec<_> = names.into_iter().collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn classes_read_from_both_quote_styles() {
+        let text = r#"<ui:UXML xmlns:ui="UnityEngine.UIElements">
+    <ui:VisualElement class="toolbar  toolbar--dark" name="root">
+        <ui:Button class='primary-button' text="class=&quot;fake&quot;" />
+        <ui:Label
+            class = "title"
+        />
+    </ui:VisualElement>
+</ui:UXML>
+"#;
+        assert_eq!(
+            sorted(class_names(text)),
+            ["primary-button", "title", "toolbar", "toolbar--dark"]
+        );
+    }
+
+    #[test]
+    fn other_attributes_and_comments_ignored() {
+        let text = r#"<!-- <ui:Label class="commented" /> -->
+<ui:ListView item-class="row" class="list {binding} 9lives" />
+"#;
+        assert_eq!(sorted(class_names(text)), ["list"]);
+    }
+}
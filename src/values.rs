@@ -0,0 +1,776 @@
+//! Value grammars for USS Language Server
+//!
+//! Structured parsing and validation for property values whose grammar is
+//! more than a flat keyword list. Offsets are byte offsets within the value.
+
+use crate::tokenizer::{tokenize, Token, TokenKind};
+
+/// A whitespace-separated component of a property value
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueComponent<'a> {
+    pub text: &'a str,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A problem found in a property value
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueError {
+    pub start: usize,
+    pub end: usize,
+    pub message: String,
+}
+
+/// Axis set by a transform-origin component
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// Horizontal (x) axis
+    X,
+    /// Vertical (y) axis
+    Y,
+    /// `center`, which can set either axis
+    Either,
+}
+
+/// Keywords that can appear in the x slot of transform-origin
+pub const TRANSFORM_ORIGIN_X_KEYWORDS: &[&str] = &["left", "center", "right"];
+
+/// Keywords that can appear in the y slot of transform-origin
+pub const TRANSFORM_ORIGIN_Y_KEYWORDS: &[&str] = &["top", "center", "bottom"];
+
+/// Split a value into whitespace-separated components
+pub fn split_components(value: &str) -> Vec<ValueComponent<'_>> {
+    let mut components = Vec::new();
+    let mut start = None;
+
+    for (i, c) in value.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                components.push(ValueComponent {
+                    text: &value[s..i],
+                    start: s,
+                    end: i,
+                });
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        components.push(ValueComponent {
+            text: &value[s..],
+            start: s,
+            end: value.len(),
+        });
+    }
+
+    components
+}
+
+/// Check whether a component is a length or percentage (`10px`, `50%`, `0`)
+pub fn is_length_or_percentage(text: &str) -> bool {
+    let number_end = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(number_end);
+    if number.parse::<f64>().is_err() {
+        return false;
+    }
+    matches!(unit, "" | "px" | "%")
+}
+
+/// Get the axis set by a transform-origin keyword
+pub fn transform_origin_keyword_axis(keyword: &str) -> Option<Axis> {
+    match keyword {
+        "left" | "right" => Some(Axis::X),
+        "top" | "bottom" => Some(Axis::Y),
+        "center" => Some(Axis::Either),
+        _ => None,
+    }
+}
+
+/// Resolve which axis each transform-origin component sets
+///
+/// Returns `None` for components that are neither keywords nor lengths.
+pub fn transform_origin_axes(components: &[ValueComponent]) -> Vec<Option<Axis>> {
+    let kinds: Vec<Option<Axis>> = components
+        .iter()
+        .map(|c| {
+            transform_origin_keyword_axis(c.text)
+                .or_else(|| is_length_or_percentage(c.text).then_some(Axis::Either))
+        })
+        .collect();
+
+    match kinds.as_slice() {
+        // A single length sets x; a single keyword sets its own axis
+        [Some(Axis::Either)] if components[0].text != "center" => vec![Some(Axis::X)],
+        // Two keywords for the same axis are invalid; report them as written
+        [Some(a), Some(b)] if a == b && *a != Axis::Either => kinds,
+        // Keywords may be given in either order (`top left`)
+        [Some(Axis::Y), Some(_)] | [Some(_), Some(Axis::X)] => vec![Some(Axis::Y), Some(Axis::X)],
+        [Some(_), Some(_)] => vec![Some(Axis::X), Some(Axis::Y)],
+        _ => kinds,
+    }
+}
+
+/// Validate a transform-origin value
+///
+/// Accepts an x component (keyword, length, or percentage) followed by an
+/// optional y component, or two keywords in either order.
+pub fn validate_transform_origin(value: &str) -> Vec<ValueError> {
+    let mut errors = Vec::new();
+    if value.contains("var(") {
+        return errors;
+    }
+
+    let components = split_components(value);
+    for extra in components.iter().skip(2) {
+        errors.push(ValueError {
+            start: extra.start,
+            end: extra.end,
+            message: "transform-origin accepts at most two values (x and y)".to_string(),
+        });
+    }
+
+    let slots = &components[..components.len().min(2)];
+    for component in slots {
+        if transform_origin_keyword_axis(component.text).is_none()
+            && !is_length_or_percentage(component.text)
+        {
+            errors.push(ValueError {
+                start: component.start,
+                end: component.end,
+                message: format!(
+                    "Invalid transform-origin value '{}'. Expected a length, percentage, or one of left, center, right, top, bottom",
+                    component.text
+                ),
+            });
+        }
+    }
+    if !errors.is_empty() || slots.len() < 2 {
+        return errors;
+    }
+
+    let first = transform_origin_keyword_axis(slots[0].text);
+    let second = transform_origin_keyword_axis(slots[1].text);
+    match (first, second) {
+        (Some(a), Some(b)) if a == b && a != Axis::Either => {
+            errors.push(ValueError {
+                start: slots[1].start,
+                end: slots[1].end,
+                message: format!(
+                    "'{}' sets the {} axis, which is already set by '{}'",
+                    slots[1].text,
+                    axis_name(a),
+                    slots[0].text
+                ),
+            });
+        }
+        // A length in the first slot always sets x, so the second must set y
+        (None, Some(Axis::X)) => {
+            errors.push(ValueError {
+                start: slots[1].start,
+                end: slots[1].end,
+                message: format!(
+                    "'{}' cannot follow a length; the second value sets the vertical axis",
+                    slots[1].text
+                ),
+            });
+        }
+        // A length in the second slot always sets y, so the first must set x
+        (Some(Axis::Y), None) => {
+            errors.push(ValueError {
+                start: slots[0].start,
+                end: slots[0].end,
+                message: format!(
+                    "'{}' cannot precede a length; the first value sets the horizontal axis",
+                    slots[0].text
+                ),
+            });
+        }
+        _ => {}
+    }
+
+    errors
+}
+
+/// Keywords to offer for a transform-origin slot, given the components before it
+pub fn transform_origin_slot_keywords(previous: &[ValueComponent]) -> &'static [&'static str] {
+    match previous {
+        [] => &["left", "center", "right", "top", "bottom"],
+        [first] if transform_origin_keyword_axis(first.text) == Some(Axis::Y) => {
+            TRANSFORM_ORIGIN_X_KEYWORDS
+        }
+        [_] => TRANSFORM_ORIGIN_Y_KEYWORDS,
+        _ => &[],
+    }
+}
+
+/// Human-readable name of an axis
+pub fn axis_name(axis: Axis) -> &'static str {
+    match axis {
+        Axis::X => "horizontal (x)",
+        Axis::Y => "vertical (y)",
+        Axis::Either => "horizontal or vertical",
+    }
+}
+
+/// Separator between the components of a multi-value property
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Separator {
+    Space,
+    Comma,
+}
+
+impl Separator {
+    /// Plural name used in messages
+    pub fn plural(self) -> &'static str {
+        match self {
+            Separator::Space => "spaces",
+            Separator::Comma => "commas",
+        }
+    }
+}
+
+/// How the components of a property value are separated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueLayout {
+    /// A fixed tuple separated by spaces (`margin: 1px 2px`)
+    SpaceTuple,
+    /// A list separated by commas (`transition-property: opacity, scale`)
+    CommaList,
+    /// Comma-separated transitions, each a space-separated group (`transition`)
+    Transition,
+}
+
+/// Properties whose values are space-separated tuples
+const SPACE_TUPLE_PROPERTIES: &[&str] = &[
+    "margin",
+    "padding",
+    "border-width",
+    "border-color",
+    "border-radius",
+    "flex",
+    "scale",
+    "translate",
+    "transform-origin",
+];
+
+/// Properties whose values are comma-separated lists
+const COMMA_LIST_PROPERTIES: &[&str] = &[
+    "transition-property",
+    "transition-duration",
+    "transition-timing-function",
+    "transition-delay",
+];
+
+/// Timing function keywords of transitions
+pub const TIMING_FUNCTIONS: &[&str] = &[
+    "ease",
+    "ease-in",
+    "ease-out",
+    "ease-in-out",
+    "linear",
+    "ease-in-sine",
+    "ease-out-sine",
+    "ease-in-out-sine",
+    "ease-in-cubic",
+    "ease-out-cubic",
+    "ease-in-out-cubic",
+    "ease-in-circ",
+    "ease-out-circ",
+    "ease-in-out-circ",
+    "ease-in-elastic",
+    "ease-out-elastic",
+    "ease-in-out-elastic",
+    "ease-in-back",
+    "ease-out-back",
+    "ease-in-out-back",
+    "ease-in-bounce",
+    "ease-out-bounce",
+    "ease-in-out-bounce",
+];
+
+/// Get the separator layout of a property, if it takes multiple values
+pub fn value_layout(property: &str) -> Option<ValueLayout> {
+    if property == "transition" {
+        Some(ValueLayout::Transition)
+    } else if SPACE_TUPLE_PROPERTIES.contains(&property) {
+        Some(ValueLayout::SpaceTuple)
+    } else if COMMA_LIST_PROPERTIES.contains(&property) {
+        Some(ValueLayout::CommaList)
+    } else {
+        None
+    }
+}
+
+/// A top-level item of a value, with the separator that precedes it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeparatedItem<'a> {
+    pub text: &'a str,
+    pub start: usize,
+    pub end: usize,
+    /// Separator before the item and its byte range; `None` for the first item
+    pub separator: Option<(Separator, usize, usize)>,
+}
+
+/// Split a value into items at top-level spaces and commas
+///
+/// Separators inside parentheses (`rgb(1, 2, 3)`) and strings are not split
+/// on. A comma surrounded by whitespace counts as a single comma separator
+/// whose range is the comma itself.
+pub fn split_separated(value: &str) -> Vec<SeparatedItem<'_>> {
+    let mut items: Vec<SeparatedItem> = Vec::new();
+    let mut pending = None;
+    let mut depth = 0;
+
+    for token in tokenize(value) {
+        if depth == 0 {
+            match token.kind {
+                TokenKind::Whitespace | TokenKind::Comment => {
+                    if pending.is_none() && !items.is_empty() {
+                        pending = Some((Separator::Space, token.start, token.end));
+                    }
+                    continue;
+                }
+                TokenKind::Delim(',') => {
+                    pending = Some((Separator::Comma, token.start, token.end));
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        match items.last_mut() {
+            Some(item) if depth > 0 || pending.is_none() => item.end = token.end,
+            _ => items.push(SeparatedItem {
+                text: "",
+                start: token.start,
+                end: token.end,
+                separator: pending.take(),
+            }),
+        }
+
+        match token.kind {
+            TokenKind::Delim('(') => depth += 1,
+            TokenKind::Delim(')') if depth > 0 => depth -= 1,
+            _ => {}
+        }
+    }
+
+    for item in &mut items {
+        item.text = &value[item.start..item.end];
+    }
+    items
+}
+
+/// A separator that does not match the property's value layout
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeparatorError {
+    /// Byte range of the separator within the value
+    pub start: usize,
+    pub end: usize,
+    pub found: Separator,
+    pub expected: Separator,
+}
+
+/// Find separators that do not match a property's layout
+///
+/// Space tuples must not contain commas and comma lists must not contain
+/// spaces between items. In the `transition` shorthand, a space between two
+/// property names should be a comma, and a comma directly after a property
+/// name that has no duration yet should be a space (`opacity, 0.3s`).
+pub fn validate_separators(property: &str, value: &str) -> Vec<SeparatorError> {
+    let Some(layout) = value_layout(property) else {
+        return Vec::new();
+    };
+    let items = split_separated(value);
+    let error = |(found, start, end): (Separator, usize, usize), expected| SeparatorError {
+        start,
+        end,
+        found,
+        expected,
+    };
+
+    match layout {
+        ValueLayout::SpaceTuple => items
+            .iter()
+            .filter_map(|item| item.separator)
+            .filter(|(found, _, _)| *found == Separator::Comma)
+            .map(|separator| error(separator, Separator::Space))
+            .collect(),
+        ValueLayout::CommaList => items
+            .iter()
+            .filter_map(|item| item.separator)
+            .filter(|(found, _, _)| *found == Separator::Space)
+            .map(|separator| error(separator, Separator::Comma))
+            .collect(),
+        ValueLayout::Transition => validate_transition_separators(&items)
+            .into_iter()
+            .map(|(separator, expected)| error(separator, expected))
+            .collect(),
+    }
+}
+
+/// Role of a component in a single transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransitionPart {
+    Property,
+    Time,
+    TimingFunction,
+}
+
+fn transition_part(text: &str) -> TransitionPart {
+    let lower = text.to_ascii_lowercase();
+    let is_time = ["ms", "s"].iter().any(|unit| {
+        lower
+            .strip_suffix(unit)
+            .is_some_and(|number| number.parse::<f64>().is_ok())
+    });
+
+    if is_time || lower == "0" {
+        TransitionPart::Time
+    } else if TIMING_FUNCTIONS.contains(&lower.as_str())
+        || lower.starts_with("cubic-bezier(")
+        || lower.starts_with("steps(")
+    {
+        TransitionPart::TimingFunction
+    } else {
+        TransitionPart::Property
+    }
+}
+
+fn validate_transition_separators(
+    items: &[SeparatedItem],
+) -> Vec<((Separator, usize, usize), Separator)> {
+    let mut errors = Vec::new();
+    // Parts of the transition being read
+    let mut group: Vec<TransitionPart> = Vec::new();
+
+    for item in items {
+        if item.text.contains("var(") {
+            group.clear();
+            continue;
+        }
+        let part = transition_part(item.text);
+
+        match item.separator {
+            Some(separator @ (Separator::Space, _, _))
+                if part == TransitionPart::Property
+                    && group.contains(&TransitionPart::Property) =>
+            {
+                errors.push((separator, Separator::Comma));
+                group.clear();
+            }
+            Some(separator @ (Separator::Comma, _, _)) => {
+                let continues_previous = part != TransitionPart::Property
+                    && group.contains(&TransitionPart::Property)
+                    && !group.contains(&TransitionPart::Time);
+                if continues_previous {
+                    errors.push((separator, Separator::Space));
+                } else {
+                    group.clear();
+                }
+            }
+            _ => {}
+        }
+
+        group.push(part);
+    }
+
+    errors
+}
+
+/// Find the literal values in a declaration value
+///
+/// Literals are numbers, hashes, strings, and function calls such as
+/// `rgb(30, 30, 30)` or `url("a.png")`. Keywords are not literals, and
+/// `var()` references, including their fallbacks, are skipped entirely.
+/// Plain integers and unitless zeros such as `0` or `1` are too common to be
+/// worth a variable, so they are not literals either.
+pub fn value_literals(value: &str) -> Vec<ValueComponent<'_>> {
+    let tokens: Vec<Token> = tokenize(value)
+        .into_iter()
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .collect();
+    let mut literals = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = tokens[i];
+        let is_call = token.kind == TokenKind::Ident
+            && tokens
+                .get(i + 1)
+                .is_some_and(|t| t.kind == TokenKind::Delim('('));
+
+        if is_call {
+            let close = matching_paren(&tokens, i + 1);
+            if !token.text(value).eq_ignore_ascii_case("var") {
+                let end = close.map_or(value.len(), |c| tokens[c].end);
+                literals.push(ValueComponent {
+                    text: &value[token.start..end],
+                    start: token.start,
+                    end,
+                });
+            }
+            i = close.map_or(tokens.len(), |c| c + 1);
+            continue;
+        }
+
+        let is_literal = match token.kind {
+            TokenKind::Number => !is_plain_number(token.text(value)),
+            TokenKind::Hash | TokenKind::String => true,
+            _ => false,
+        };
+        if is_literal {
+            literals.push(ValueComponent {
+                text: token.text(value),
+                start: token.start,
+                end: token.end,
+            });
+        }
+        i += 1;
+    }
+
+    literals
+}
+
+/// Whether a number is a plain integer or a unitless zero
+fn is_plain_number(number: &str) -> bool {
+    number.parse::<i64>().is_ok() || number.parse::<f64>().is_ok_and(|n| n == 0.0)
+}
+
+/// Index of the `)` closing the `(` at `open`
+fn matching_paren(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        match token.kind {
+            TokenKind::Delim('(') => depth += 1,
+            TokenKind::Delim(')') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Key under which two literals are considered the same value
+///
+/// Hex colors compare case-insensitively and whitespace inside function calls
+/// is ignored.
+pub fn literal_key(literal: &str) -> String {
+    if literal.starts_with('#') {
+        literal.to_ascii_lowercase()
+    } else {
+        literal.chars().filter(|c| !c.is_whitespace()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform_origin_errors(value: &str) -> Vec<String> {
+        validate_transform_origin(value)
+            .into_iter()
+            .map(|error| value[error.start..error.end].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn transform_origin_accepts_keywords_and_lengths() {
+        for value in [
+            "center",
+            "left",
+            "bottom",
+            "10px",
+            "50%",
+            "0",
+            "left top",
+            "top left",
+            "center bottom",
+            "right center",
+            "10px 20%",
+            "left 10px",
+            "10px bottom",
+            "var(--origin)",
+        ] {
+            assert!(transform_origin_errors(value).is_empty(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn transform_origin_rejects_invalid_combinations() {
+        // Two keywords for the same axis
+        assert_eq!(transform_origin_errors("left right"), ["right"]);
+        assert_eq!(transform_origin_errors("top bottom"), ["bottom"]);
+        // A length sets x first, then y
+        assert_eq!(transform_origin_errors("10px left"), ["left"]);
+        assert_eq!(transform_origin_errors("top 10px"), ["top"]);
+        // Unknown components and units
+        assert_eq!(transform_origin_errors("middle"), ["middle"]);
+        assert_eq!(transform_origin_errors("10em 5px"), ["10em"]);
+        // At most two values
+        assert_eq!(transform_origin_errors("left top 0"), ["0"]);
+    }
+
+    fn literals(value: &str) -> Vec<&str> {
+        value_literals(value)
+            .into_iter()
+            .map(|literal| literal.text)
+            .collect()
+    }
+
+    #[test]
+    fn literals_include_numbers_with_units_colors_strings_and_calls() {
+        assert_eq!(
+            literals("10px 0.5 50% #FFF \"a\" rgb(30, 30, 30)"),
+            ["10px", "0.5", "50%", "#FFF", "\"a\"", "rgb(30, 30, 30)"]
+        );
+        assert_eq!(literals("0px 0s"), ["0px", "0s"]);
+    }
+
+    #[test]
+    fn literals_skip_plain_integers_and_unitless_zeros() {
+        assert!(literals("0").is_empty());
+        assert!(literals("0 0 0 0").is_empty());
+        assert!(literals("1 -1 +2 0.0").is_empty());
+        assert_eq!(literals("0 4px 0 4px"), ["4px", "4px"]);
+    }
+
+    #[test]
+    fn literals_skip_keywords_and_var_references() {
+        assert!(literals("auto flex-start").is_empty());
+        assert_eq!(literals("var(--size, 10px) 12px"), ["12px"]);
+    }
+
+    /// Items as `text` or `<separator>text`, with `,` or `_` for the separator
+    fn items(value: &str) -> Vec<String> {
+        split_separated(value)
+            .into_iter()
+            .map(|item| match item.separator {
+                Some((Separator::Comma, start, end)) => {
+                    assert_eq!(&value[start..end], ",");
+                    format!(",{}", item.text)
+                }
+                Some((Separator::Space, start, end)) => {
+                    assert!(value[start..end].trim().is_empty());
+                    format!("_{}", item.text)
+                }
+                None => item.text.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_at_top_level_spaces_and_commas() {
+        assert_eq!(items("10px 5px"), ["10px", "_5px"]);
+        assert_eq!(items("10px,5px"), ["10px", ",5px"]);
+        assert_eq!(
+            items("opacity 0.3s , scale 1s"),
+            ["opacity", "_0.3s", ",scale", "_1s"]
+        );
+        assert_eq!(items("  a   b  "), ["a", "_b"]);
+    }
+
+    #[test]
+    fn split_keeps_functions_and_strings_whole() {
+        assert_eq!(
+            items("rgb(1, 2, 3) cubic-bezier(0.1, 0.7, 1, 0.1)"),
+            ["rgb(1, 2, 3)", "_cubic-bezier(0.1, 0.7, 1, 0.1)"]
+        );
+        assert_eq!(
+            items("\"a, b\", url(c d.png)"),
+            ["\"a, b\"", ",url(c d.png)"]
+        );
+    }
+
+    /// Wrong separators as `found>expected@start`
+    fn separator_errors(property: &str, value: &str) -> Vec<String> {
+        validate_separators(property, value)
+            .into_iter()
+            .map(|error| {
+                let name = |separator| match separator {
+                    Separator::Space => "space",
+                    Separator::Comma => "comma",
+                };
+                format!(
+                    "{}>{}@{}",
+                    name(error.found),
+                    name(error.expected),
+                    error.start
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn commas_flagged_in_space_tuples() {
+        assert_eq!(separator_errors("margin", "10px, 5px"), ["comma>space@4"]);
+        assert_eq!(
+            separator_errors("border-radius", "1px,2px , 3px"),
+            ["comma>space@3", "comma>space@8"]
+        );
+        assert!(separator_errors("margin", "10px 5px").is_empty());
+        assert!(separator_errors("border-color", "rgb(1, 2, 3) red").is_empty());
+    }
+
+    #[test]
+    fn spaces_flagged_in_comma_lists() {
+        assert_eq!(
+            separator_errors("transition-property", "opacity translate"),
+            ["space>comma@7"]
+        );
+        assert_eq!(
+            separator_errors("transition-duration", "1s, 2s 3s"),
+            ["space>comma@6"]
+        );
+        assert!(separator_errors("transition-property", "opacity, translate").is_empty());
+        assert!(separator_errors(
+            "transition-timing-function",
+            "ease , cubic-bezier(0, 0, 1, 1)"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn transition_shorthand_separators() {
+        for value in [
+            "opacity 0.3s",
+            "opacity 0.3s ease-in, scale 1s 0.5s",
+            "opacity 0.3s,scale 1s",
+            "all 200ms cubic-bezier(0.1, 0.7, 1, 0.1)",
+            "opacity var(--duration) ease, scale 1s",
+            "none",
+        ] {
+            assert!(
+                separator_errors("transition", value).is_empty(),
+                "{}",
+                value
+            );
+        }
+        assert_eq!(
+            separator_errors("transition", "opacity scale 0.3s"),
+            ["space>comma@7"]
+        );
+        assert_eq!(
+            separator_errors("transition", "opacity, 0.3s ease"),
+            ["comma>space@7"]
+        );
+        assert_eq!(
+            separator_errors("transition", "opacity 1s, scale, 2s"),
+            ["comma>space@17"]
+        );
+    }
+
+    #[test]
+    fn separators_not_checked_for_other_properties() {
+        assert!(separator_errors("color", "rgb(1, 2, 3)").is_empty());
+        assert!(separator_errors("font-size", "12px, 14px").is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+//! Virtual documents for USS Language Server
+//!
+//! Caches read-only generated content (style explanations, formatting
+//! previews) under `uss-preview://` URIs. Clients fetch the content with the
+//! `uss/virtualContent` request; entries are dropped when their source
+//! document changes or closes, when the generated document itself is closed,
+//! and oldest first once the cache holds [`CAPACITY`] entries.
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tower_lsp::lsp_types::Url;
+
+/// URI scheme of generated documents
+pub const SCHEME: &str = "uss-preview";
+
+/// Maximum number of cached documents
+///
+/// Reports generated from the whole workspace have no source document to
+/// invalidate them, so the cache must be bounded on its own.
+pub const CAPACITY: usize = 64;
+
+/// Parameters of the `uss/virtualContent` request
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualContentParams {
+    pub uri: String,
+}
+
+/// Result of the `uss/virtualContent` request
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualContent {
+    /// Generated text
+    pub content: String,
+    /// Language of the generated text (`markdown`, `uss`)
+    pub language_id: String,
+    /// The document the content was generated from
+    pub source_uri: String,
+}
+
+/// Cache of generated documents keyed by their `uss-preview://` URI
+#[derive(Debug, Default)]
+pub struct VirtualDocuments {
+    /// Content with the ID it was created with, to evict the oldest first
+    documents: DashMap<String, (u64, VirtualContent)>,
+    next_id: AtomicU64,
+}
+
+impl VirtualDocuments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store generated content and return the URI it can be fetched from
+    ///
+    /// `kind` names the generator and becomes the URI authority, e.g.
+    /// `uss-preview://explain/3.md`.
+    pub fn create(&self, kind: &str, source_uri: &str, language_id: &str, content: String) -> Url {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let extension = if language_id == "markdown" {
+            "md"
+        } else {
+            language_id
+        };
+        let uri = format!("{}://{}/{}.{}", SCHEME, kind, id, extension);
+
+        self.documents.insert(
+            uri.clone(),
+            (
+                id,
+                VirtualContent {
+                    content,
+                    language_id: language_id.to_string(),
+                    source_uri: source_uri.to_string(),
+                },
+            ),
+        );
+        while self.documents.len() > CAPACITY {
+            self.evict_oldest();
+        }
+
+        uri.parse().expect("virtual document URI is valid")
+    }
+
+    /// Get the content of a generated document, if it is still valid
+    pub fn get(&self, uri: &str) -> Option<VirtualContent> {
+        self.documents.get(uri).map(|d| d.1.clone())
+    }
+
+    /// Drop a generated document, e.g. when the client closes it
+    pub fn remove(&self, uri: &str) {
+        self.documents.remove(uri);
+    }
+
+    /// Drop every generated document derived from a source document
+    pub fn invalidate_source(&self, source_uri: &str) {
+        self.documents.retain(|_, d| d.1.source_uri != source_uri);
+    }
+
+    fn evict_oldest(&self) {
+        let oldest = self
+            .documents
+            .iter()
+            .min_by_key(|entry| entry.0)
+            .map(|entry| entry.key().clone());
+        if let Some(uri) = oldest {
+            self.documents.remove(&uri);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetches_created_content() {
+        let documents = VirtualDocuments::new();
+        let uri = documents.create("explain", "file:///a.uss", "markdown", "# A".into());
+
+        assert_eq!(uri.scheme(), SCHEME);
+        assert!(uri.as_str().ends_with(".md"));
+        let content = documents.get(uri.as_str()).unwrap();
+        assert_eq!(content.content, "# A");
+        assert_eq!(content.language_id, "markdown");
+        assert_eq!(content.source_uri, "file:///a.uss");
+    }
+
+    #[test]
+    fn invalidates_documents_of_a_source() {
+        let documents = VirtualDocuments::new();
+        let a = documents.create("explain", "file:///a.uss", "markdown", "a".into());
+        let b = documents.create("formatted", "file:///b.uss", "uss", "b".into());
+
+        documents.invalidate_source("file:///a.uss");
+
+        assert!(documents.get(a.as_str()).is_none());
+        assert!(documents.get(b.as_str()).is_some());
+    }
+
+    #[test]
+    fn removes_closed_documents() {
+        let documents = VirtualDocuments::new();
+        let report = documents.create("report", "", "markdown", "report".into());
+
+        documents.remove(report.as_str());
+
+        assert!(documents.get(report.as_str()).is_none());
+    }
+
+    #[test]
+    fn evicts_the_oldest_documents_beyond_capacity() {
+        let documents = VirtualDocuments::new();
+        let uris: Vec<Url> = (0..CAPACITY + 2)
+            .map(|i| documents.create("report", "", "markdown", i.to_string()))
+            .collect();
+
+        assert_eq!(documents.documents.len(), CAPACITY);
+        assert!(documents.get(uris[0].as_str()).is_none());
+        assert!(documents.get(uris[1].as_str()).is_none());
+        for uri in &uris[2..] {
+            assert!(documents.get(uri.as_str()).is_some());
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! WebAssembly exports for USS Language Server
+//!
+//! JavaScript bindings of the analysis for running diagnostics in the browser,
+//! e.g. in the docs playground. Results cross the boundary as JSON strings in
+//! the same shape as the serde serialization of [`AnalysisResult`].
+//!
+//! [`AnalysisResult`]: crate::analysis::AnalysisResult
+
+use crate::analysis::{analyze, AnalysisOptions};
+use crate::document::{formatted_text, Document};
+use crate::settings::FormatSettings;
+use lsp_types::FormattingOptions;
+use wasm_bindgen::prelude::*;
+
+/// Analyze a style sheet with default options, returning the result as JSON
+#[wasm_bindgen]
+pub fn analyze_json(text: &str) -> String {
+    let result = analyze(text, &AnalysisOptions::default());
+    serde_json::to_string(&result).unwrap_or_default()
+}
+
+/// Format a style sheet, indenting with `tab_size` spaces
+#[wasm_bindgen]
+pub fn format(text: &str, tab_size: u32) -> String {
+    let doc = Document::new(text.to_string(), 0);
+    let options = FormattingOptions {
+        tab_size,
+        insert_spaces: true,
+        ..Default::default()
+    };
+    formatted_text(&doc, &options, FormatSettings::default())
+}
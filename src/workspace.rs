@@ -0,0 +1,414 @@
+//! Workspace index for USS Language Server
+//!
+//! Keeps the style sheets of the workspace in memory, including files that are
+//! not open in the editor, so cross-file features such as variable completion
+//! and undefined-variable checks can see them. Classes assigned in UXML files
+//! are kept too, for class completion.
+
+use crate::document::{defined_variables, get_colors, variable_usages, Document};
+use crate::parser::parse;
+use crate::position_codec::PositionCodec;
+use crate::uxml;
+use dashmap::DashMap;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+use tower_lsp::lsp_types::{Color, Range, Url};
+
+/// File extensions indexed by the workspace scan (style sheets and theme style sheets)
+const INDEXED_EXTENSIONS: &[&str] = &["uss", "tss"];
+
+/// File extension of UXML documents, whose classes are indexed
+const UXML_EXTENSION: &str = "uxml";
+
+/// Directories skipped by the workspace scan
+const IGNORED_DIRECTORIES: &[&str] = &[
+    ".git",
+    "Library",
+    "Temp",
+    "Logs",
+    "obj",
+    "Build",
+    "node_modules",
+];
+
+/// A style sheet known to the workspace index
+#[derive(Debug, Clone)]
+pub struct IndexedFile {
+    /// Names of the variables defined in the file
+    pub variables: HashSet<String>,
+    /// Variable declarations inside rules, in source order
+    pub definitions: Vec<VariableDefinition>,
+    /// Number of var() usages of each variable
+    pub usages: HashMap<String, usize>,
+    /// Literal colors in declaration values, in source order
+    pub colors: Vec<ColorUsage>,
+}
+
+/// A variable declaration (`--name: value;`)
+#[derive(Debug, Clone)]
+pub struct VariableDefinition {
+    pub name: String,
+    pub value: String,
+    /// Zero-based line of the declaration
+    pub line: usize,
+}
+
+/// A literal color (`#FF3366`, `rgba(...)`) in a declaration value
+#[derive(Debug, Clone)]
+pub struct ColorUsage {
+    pub color: Color,
+    /// The color as written
+    pub text: String,
+    pub range: Range,
+    /// Selector of the rule containing the color
+    pub selector: String,
+}
+
+impl IndexedFile {
+    /// Index the content of a style sheet, with ranges in a position encoding
+    pub fn from_text(text: &str, codec: PositionCodec) -> Self {
+        Self::from_document(&Document::new(text.to_string(), 0).with_codec(codec))
+    }
+
+    /// Index an open document, with ranges in its position encoding
+    pub fn from_document(doc: &Document) -> Self {
+        let text = &doc.get_text();
+        let stylesheet = parse(text);
+        let definitions = stylesheet
+            .rules
+            .iter()
+            .flat_map(|rule| &rule.declarations)
+            .filter(|decl| decl.property.starts_with("--"))
+            .map(|decl| VariableDefinition {
+                name: decl.property.clone(),
+                value: decl.value.clone(),
+                line: text[..decl.property_start].matches('\n').count(),
+            })
+            .collect();
+        let colors = get_colors(doc)
+            .into_iter()
+            .filter_map(|info| {
+                let start = doc.position_to_byte_offset(info.range.start)?;
+                let end = doc.position_to_byte_offset(info.range.end)?;
+                Some(ColorUsage {
+                    color: info.color,
+                    text: text[start..end].to_string(),
+                    range: info.range,
+                    selector: stylesheet.rule_at(start)?.selector.clone(),
+                })
+            })
+            .collect();
+
+        Self {
+            variables: defined_variables(text),
+            definitions,
+            usages: variable_usages(text),
+            colors,
+        }
+    }
+}
+
+/// Index of the style sheets and UXML documents in the workspace
+#[derive(Debug, Default)]
+pub struct WorkspaceIndex {
+    /// Indexed files by URI
+    files: DashMap<String, IndexedFile>,
+    /// Classes assigned in UXML documents by URI
+    uxml_classes: DashMap<String, HashSet<String>>,
+    /// Position encoding of the ranges in indexed files
+    codec: RwLock<PositionCodec>,
+}
+
+impl WorkspaceIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the position encoding negotiated with the client
+    ///
+    /// Files indexed earlier keep their ranges, so set it before indexing.
+    pub fn set_codec(&self, codec: PositionCodec) {
+        *self.codec.write().unwrap() = codec;
+    }
+
+    /// Read a file from disk and add it to the index, replacing any previous entry
+    ///
+    /// UXML documents are indexed for their classes, anything else as a style sheet.
+    pub fn index_file(&self, path: &Path) -> io::Result<()> {
+        let text = std::fs::read_to_string(path)?;
+        let uri = Url::from_file_path(path)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path is not absolute"))?;
+        if is_uxml_file(path) {
+            self.uxml_classes
+                .insert(uri.to_string(), uxml::class_names(&text));
+        } else {
+            self.insert(uri.to_string(), text);
+        }
+        Ok(())
+    }
+
+    /// Add a file with the given content to the index
+    pub fn insert(&self, uri: String, text: String) {
+        let codec = *self.codec.read().unwrap();
+        self.files.insert(uri, IndexedFile::from_text(&text, codec));
+    }
+
+    /// Remove a file from the index
+    pub fn remove(&self, uri: &str) {
+        self.files.remove(uri);
+        self.uxml_classes.remove(uri);
+    }
+
+    /// Names of all classes assigned in indexed UXML documents
+    pub fn uxml_classes(&self) -> HashSet<String> {
+        self.uxml_classes
+            .iter()
+            .flat_map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    /// Names of all variables defined in indexed files other than `exclude_uri`
+    pub fn variables(&self, exclude_uri: &str) -> HashSet<String> {
+        self.files
+            .iter()
+            .filter(|entry| entry.key() != exclude_uri)
+            .flat_map(|entry| entry.variables.clone())
+            .collect()
+    }
+
+    /// Copy of every indexed file, sorted by URI
+    pub fn snapshot(&self) -> Vec<(String, IndexedFile)> {
+        let mut files: Vec<_> = self
+            .files
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        files
+    }
+
+    /// Index every style sheet and UXML document under a directory
+    ///
+    /// Fails if the root itself cannot be read. Unreadable files and
+    /// subdirectories below it are skipped and counted.
+    pub fn scan(&self, root: &Path) -> io::Result<ScanSummary> {
+        let mut summary = ScanSummary::default();
+        let mut pending = vec![root.to_path_buf()];
+
+        while let Some(dir) = pending.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) if dir == root => return Err(e),
+                Err(e) => {
+                    log::warn!("Skipping {}: {}", dir.display(), e);
+                    summary.skipped += 1;
+                    continue;
+                }
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    if !is_ignored_directory(&path) {
+                        pending.push(path);
+                    }
+                } else if is_indexed_file(&path) {
+                    match self.index_file(&path) {
+                        Ok(()) => summary.indexed += 1,
+                        Err(e) => {
+                            log::warn!("Skipping {}: {}", path.display(), e);
+                            summary.skipped += 1;
+                        }
+                    }
+                } else if is_uxml_file(&path) {
+                    match self.index_file(&path) {
+                        Ok(()) => summary.uxml_indexed += 1,
+                        Err(e) => {
+                            log::warn!("Skipping {}: {}", path.display(), e);
+                            summary.uxml_skipped += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Outcome of a workspace scan
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanSummary {
+    /// Style sheets added to the index
+    pub indexed: usize,
+    /// Style sheets and directories that could not be read
+    pub skipped: usize,
+    /// UXML documents whose classes were added to the index
+    pub uxml_indexed: usize,
+    /// UXML documents that could not be read
+    pub uxml_skipped: usize,
+}
+
+/// Names of variables defined outside a document
+///
+/// Combines the workspace index with the live content of other open documents.
+pub fn workspace_variables(
+    index: &WorkspaceIndex,
+    documents: &DashMap<String, Document>,
+    uri: &str,
+) -> HashSet<String> {
+    let mut variables = index.variables(uri);
+    for entry in documents.iter().filter(|e| e.key() != uri) {
+        variables.extend(defined_variables(&entry.get_text()));
+    }
+    variables
+}
+
+/// All style sheets of the workspace, with open documents' live content
+/// replacing their indexed version
+pub fn workspace_files(
+    index: &WorkspaceIndex,
+    documents: &DashMap<String, Document>,
+) -> Vec<(String, IndexedFile)> {
+    let mut files: Vec<_> = index
+        .snapshot()
+        .into_iter()
+        .filter(|(uri, _)| !documents.contains_key(uri))
+        .collect();
+    for entry in documents.iter() {
+        files.push((
+            entry.key().clone(),
+            IndexedFile::from_document(entry.value()),
+        ));
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    files
+}
+
+/// Check whether a path has an indexed style sheet extension
+pub fn is_indexed_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| INDEXED_EXTENSIONS.contains(&e))
+        .unwrap_or(false)
+}
+
+/// Check whether a path is a UXML document
+pub fn is_uxml_file(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some(UXML_EXTENSION)
+}
+
+fn is_ignored_directory(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| IGNORED_DIRECTORIES.contains(&n))
+        .unwrap_or(false)
+}
+
+/// Resolve a configured path against the workspace root
+pub fn resolve_path(root: Option<&Path>, path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    match root {
+        Some(root) if path.is_relative() => root.join(path),
+        _ => path,
+    }
+}
+
+/// Resolve a path that must stay inside the workspace root
+///
+/// Only relative paths without `..` components are accepted, so the result
+/// cannot escape the root. Returns `None` without a root.
+pub fn workspace_path(root: Option<&Path>, path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path);
+    let inside = relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !inside || relative.file_name().is_none() {
+        return None;
+    }
+    Some(root?.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URI: &str = "file:///colors.uss";
+    const TEXT: &str = ".é😀 { color: #FF0000; }\n";
+
+    fn color_columns(index: &WorkspaceIndex) -> (u32, u32) {
+        let files = index.snapshot();
+        let range = files[0].1.colors[0].range;
+        (range.start.character, range.end.character)
+    }
+
+    #[test]
+    fn index_ranges_use_the_negotiated_codec() {
+        let index = WorkspaceIndex::new();
+        index.insert(URI.to_string(), TEXT.to_string());
+        // é is one UTF-16 unit, 😀 two
+        assert_eq!(color_columns(&index), (14, 21));
+
+        index.set_codec(PositionCodec::Utf8);
+        index.insert(URI.to_string(), TEXT.to_string());
+        // é is two bytes, 😀 four
+        assert_eq!(color_columns(&index), (17, 24));
+    }
+
+    #[test]
+    fn workspace_paths_stay_inside_the_root() {
+        let root = Path::new("/work");
+        assert_eq!(
+            workspace_path(Some(root), "reports/vars.md"),
+            Some(root.join("reports/vars.md"))
+        );
+        assert_eq!(
+            workspace_path(Some(root), "./vars.csv"),
+            Some(root.join("vars.csv"))
+        );
+        for path in [
+            "../vars.md",
+            "reports/../../vars.md",
+            "/tmp/vars.md",
+            "",
+            "reports/..",
+        ] {
+            assert_eq!(workspace_path(Some(root), path), None, "{}", path);
+        }
+        assert_eq!(workspace_path(None, "vars.md"), None);
+    }
+
+    #[test]
+    fn scan_indexes_uxml_classes() {
+        let root = std::env::temp_dir().join(format!("uss-uxml-scan-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("Library")).unwrap();
+        std::fs::write(root.join("theme.uss"), ".panel { }\n").unwrap();
+        std::fs::write(root.join("main.uxml"), "<ui:Button class=\"primary\" />\n").unwrap();
+        std::fs::write(root.join("broken.uxml"), [0xff, 0xfe, 0x00]).unwrap();
+        std::fs::write(
+            root.join("Library/cached.uxml"),
+            "<ui:Label class=\"cached\" />\n",
+        )
+        .unwrap();
+
+        let index = WorkspaceIndex::new();
+        let summary = index.scan(&root).unwrap();
+        assert_eq!(
+            summary,
+            ScanSummary {
+                indexed: 1,
+                skipped: 0,
+                uxml_indexed: 1,
+                uxml_skipped: 1,
+            }
+        );
+        assert_eq!(index.uxml_classes(), HashSet::from(["primary".to_string()]));
+        assert_eq!(index.snapshot().len(), 1);
+
+        let uri = Url::from_file_path(root.join("main.uxml")).unwrap();
+        index.remove(uri.as_str());
+        assert!(index.uxml_classes().is_empty());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,198 @@
+//! Test harness driving the language server binary over stdio
+
+#![allow(dead_code)]
+
+use lsp_types::Url;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for a response before failing the test
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// A running language server
+pub struct Server {
+    child: Child,
+    stdin: ChildStdin,
+    messages: Receiver<Value>,
+    next_id: u64,
+    /// Server requests answered with an error instead of `null`
+    failing: Vec<String>,
+    /// Notifications and server requests received while waiting for responses
+    pub notifications: Vec<Value>,
+}
+
+impl Server {
+    /// Start the server and complete the `initialize` handshake
+    pub fn start(initialization_options: Value) -> Self {
+        Self::start_with(initialization_options, None, json!({}), &[])
+    }
+
+    /// Start the server with a workspace root, client capabilities, and
+    /// environment variables
+    pub fn start_with(
+        initialization_options: Value,
+        root: Option<&Path>,
+        capabilities: Value,
+        env: &[(&str, &str)],
+    ) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_uss-language-server"))
+            .envs(env.iter().copied())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("start language server");
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let (sender, messages) = channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Some(message) = read_message(&mut reader) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut server = Self {
+            child,
+            stdin,
+            messages,
+            next_id: 0,
+            failing: Vec::new(),
+            notifications: Vec::new(),
+        };
+        let root_uri = root.map(|root| Url::from_file_path(root).unwrap().to_string());
+        server.request(
+            "initialize",
+            json!({
+                "capabilities": capabilities,
+                "initializationOptions": initialization_options,
+                "rootUri": root_uri,
+            }),
+        );
+        server.notify("initialized", json!({}));
+        server
+    }
+
+    /// Send a request and wait for its response (`result` or `error`)
+    ///
+    /// `null` params are omitted, for requests that take none.
+    pub fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = self.next_id;
+        let mut message = json!({ "jsonrpc": "2.0", "id": id, "method": method });
+        if !params.is_null() {
+            message["params"] = params;
+        }
+        self.send(message);
+
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            let message = self
+                .receive(deadline)
+                .unwrap_or_else(|| panic!("no response to {} within {:?}", method, TIMEOUT));
+            if message["id"] == id && message.get("method").is_none() {
+                return message;
+            }
+            self.handle(message);
+        }
+    }
+
+    /// Answer the server's `method` requests with an error from now on
+    ///
+    /// Messages are only read while waiting, so this applies to requests the
+    /// server already sent but the harness has not read yet.
+    pub fn fail_server_requests(&mut self, method: &str) {
+        self.failing.push(method.to_string());
+    }
+
+    pub fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    pub fn open(&mut self, uri: &str, text: &str) {
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": { "uri": uri, "languageId": "uss", "version": 1, "text": text }
+            }),
+        );
+    }
+
+    /// Wait for a notification matching `predicate`, returning it
+    pub fn wait_for(&mut self, predicate: impl Fn(&Value) -> bool) -> Option<Value> {
+        if let Some(index) = self.notifications.iter().position(&predicate) {
+            return Some(self.notifications.remove(index));
+        }
+        let deadline = Instant::now() + TIMEOUT;
+        while let Some(message) = self.receive(deadline) {
+            if predicate(&message) {
+                return Some(message);
+            }
+            self.handle(message);
+        }
+        None
+    }
+
+    fn receive(&mut self, deadline: Instant) -> Option<Value> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        self.messages.recv_timeout(timeout).ok()
+    }
+
+    /// Answer server requests and keep notifications
+    fn handle(&mut self, message: Value) {
+        if let (Some(id), Some(method)) = (message.get("id"), message["method"].as_str()) {
+            let reply = if self.failing.iter().any(|failing| failing == method) {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32603, "message": format!("{} rejected by the test", method) }
+                })
+            } else {
+                json!({ "jsonrpc": "2.0", "id": id, "result": null })
+            };
+            self.send(reply);
+        }
+        self.notifications.push(message);
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.stdin.flush().unwrap();
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn read_message(reader: &mut impl BufRead) -> Option<Value> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            length = value.trim().parse().ok();
+        }
+    }
+    let mut body = vec![0; length?];
+    reader.read_exact(&mut body).ok()?;
+    serde_json::from_slice(&body).ok()
+}
@@ -0,0 +1,83 @@
+//! Edits are not queued behind a burst of diagnostics for opened files
+
+mod common;
+
+use common::Server;
+use serde_json::{json, Value};
+
+const FILES: usize = 50;
+
+fn stylesheet(rules: usize) -> String {
+    (0..rules)
+        .map(|i| {
+            format!(
+                ".item-{i} > Button:hover {{\n    color: #ff{:04x};\n    margin: {i}px 2px;\n    -unity-font-style: bold;\n    transition: opacity 0.{}s ease-in;\n}}\n",
+                i % 0xffff,
+                i % 10
+            )
+        })
+        .collect()
+}
+
+fn uri(i: usize) -> String {
+    format!("file:///latency/sheet-{}.uss", i)
+}
+
+fn is_diagnostics(message: &Value) -> bool {
+    message["method"] == "textDocument/publishDiagnostics"
+}
+
+#[test]
+fn edits_are_not_queued_behind_opened_files() {
+    // One background run at a time, so the opened files drain one by one
+    let mut server = Server::start(json!({ "diagnostics": { "maxConcurrency": 1 } }));
+    let text = stylesheet(2000);
+    for i in 0..FILES {
+        server.open(&uri(i), &text);
+    }
+
+    let edited = "file:///latency/edited.uss";
+    server.open(edited, &stylesheet(10));
+    server.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": edited, "version": 2 },
+            "contentChanges": [{ "text": ".a {\n    colr: red;\n}\n" }]
+        }),
+    );
+
+    // Interactive requests are answered meanwhile
+    let params = json!({
+        "textDocument": { "uri": edited },
+        "position": { "line": 1, "character": 6 }
+    });
+    for method in ["textDocument/hover", "textDocument/completion"] {
+        let response = server.request(method, params.clone());
+        assert!(response.get("error").is_none(), "{}", response);
+    }
+
+    let published = server
+        .wait_for(|message| {
+            is_diagnostics(message)
+                && message["params"]["uri"] == edited
+                && message["params"]["version"] == 2
+        })
+        .expect("diagnostics of the edit");
+    assert!(published["params"]["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|d| d["message"].as_str().unwrap().contains("colr")));
+
+    // The edit was published while the opened files were still waiting
+    let backlog = server
+        .notifications
+        .iter()
+        .filter(|m| is_diagnostics(m) && m["params"]["uri"] != edited)
+        .count();
+    assert!(
+        backlog < FILES,
+        "published after all {} opened files",
+        backlog
+    );
+}
@@ -0,0 +1,125 @@
+//! Recovery from a panicking request handler (`uss.test.panic`)
+
+mod common;
+
+use common::Server;
+use serde_json::json;
+use std::fs;
+
+const URI: &str = "file:///panic/sheet.uss";
+
+#[test]
+fn panicking_request_fails_alone_and_logs_backtrace() {
+    let log_file = std::env::temp_dir().join(format!("uss-panic-{}.log", std::process::id()));
+    let _ = fs::remove_file(&log_file);
+    let mut server = Server::start_with(
+        json!({}),
+        None,
+        json!({}),
+        &[("USS_LOG_FILE", log_file.to_str().unwrap())],
+    );
+    server.open(URI, ".panel { color: red; }\n");
+
+    let response = server.request(
+        "workspace/executeCommand",
+        json!({ "command": "uss.test.panic", "arguments": [URI] }),
+    );
+    assert_eq!(response["error"]["code"], -32603, "{}", response);
+    assert!(response["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("uss.test.panic invoked"));
+
+    // The editor is told which request failed
+    let message = server
+        .wait_for(|message| {
+            message["method"] == "window/showMessage"
+                && message["params"]["message"]
+                    .as_str()
+                    .is_some_and(|text| text.contains("internal error"))
+        })
+        .expect("error message");
+    let text = message["params"]["message"].as_str().unwrap();
+    assert!(text.contains("workspace/executeCommand"), "{}", text);
+    assert!(text.contains(URI), "{}", text);
+
+    // The next request is served normally
+    let response = server.request(
+        "textDocument/hover",
+        json!({
+            "textDocument": { "uri": URI },
+            "position": { "line": 0, "character": 10 }
+        }),
+    );
+    assert!(response.get("error").is_none(), "{}", response);
+    assert!(response["result"]["contents"].is_object(), "{}", response);
+
+    let log = fs::read_to_string(&log_file).unwrap();
+    let _ = fs::remove_file(&log_file);
+    assert!(log.contains("uss.test.panic invoked"), "{}", log);
+    // The backtrace of the panic, not only its message
+    assert!(log.contains("std::panicking::"), "{}", log);
+    assert!(
+        log.contains("workspace/executeCommand panicked for"),
+        "{}",
+        log
+    );
+}
+
+#[test]
+fn panicking_diagnostics_keep_previous_results() {
+    let uri = "file:///panic/background.uss";
+    let mut server = Server::start(json!({}));
+    let is_published = |version: i64| {
+        move |message: &serde_json::Value| {
+            message["method"] == "textDocument/publishDiagnostics"
+                && message["params"]["uri"] == uri
+                && message["params"]["version"] == version
+        }
+    };
+    let change = |server: &mut Server, version: i64, text: &str| {
+        server.notify(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": uri, "version": version },
+                "contentChanges": [{ "text": text }]
+            }),
+        );
+    };
+
+    server.open(uri, ".a {\n    colr: red;\n}\n");
+    assert!(server.wait_for(is_published(1)).is_some());
+
+    // The background analysis panics on the marker
+    change(
+        &mut server,
+        2,
+        "/* uss.test.panic */\n.a {\n    colr: red;\n}\n",
+    );
+    let message = server
+        .wait_for(|message| {
+            message["method"] == "window/showMessage"
+                && message["params"]["message"]
+                    .as_str()
+                    .is_some_and(|text| text.contains("internal error in diagnostics"))
+        })
+        .expect("error message");
+    let text = message["params"]["message"].as_str().unwrap();
+    assert!(text.contains(uri), "{}", text);
+    assert!(text.contains("uss.test.panic found"), "{}", text);
+
+    // Requests are still served, and the next edit publishes again
+    let response = server.request(
+        "textDocument/hover",
+        json!({
+            "textDocument": { "uri": uri },
+            "position": { "line": 2, "character": 6 }
+        }),
+    );
+    assert!(response.get("error").is_none(), "{}", response);
+
+    change(&mut server, 3, ".a {\n    color: red;\n}\n");
+    assert!(server.wait_for(is_published(3)).is_some());
+    // Version 2 published nothing, so its predecessor's results stayed
+    assert!(!server.notifications.iter().any(is_published(2)));
+}
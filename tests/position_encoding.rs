@@ -0,0 +1,106 @@
+//! Providers on documents with non-ASCII text, under both position encodings
+//!
+//! Each document is exercised through UTF-8 and UTF-16 columns; positions are
+//! derived from byte offsets with the document's own codec.
+
+use lsp_types::*;
+use std::collections::HashSet;
+use uss_language_server::code_actions::get_code_actions;
+use uss_language_server::completion::get_completions;
+use uss_language_server::diagnostics::get_diagnostics;
+use uss_language_server::document::Document;
+use uss_language_server::hover::get_hover;
+use uss_language_server::position_codec::PositionCodec;
+use uss_language_server::settings::Settings;
+
+const CODECS: [PositionCodec; 2] = [PositionCodec::Utf8, PositionCodec::Utf16];
+
+fn document(text: &str, codec: PositionCodec) -> Document {
+    Document::new(text.to_string(), 1).with_codec(codec)
+}
+
+/// Position just after the first occurrence of `needle`
+fn position_after(doc: &Document, needle: &str) -> Position {
+    let text = doc.get_text();
+    let offset = text.find(needle).expect("needle in document") + needle.len();
+    doc.byte_offset_to_position(offset)
+}
+
+fn apply_edits(doc: &mut Document, mut edits: Vec<TextEdit>) -> String {
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+    for edit in edits {
+        doc.apply_change(edit.range, &edit.new_text);
+    }
+    doc.get_text()
+}
+
+#[test]
+fn text_before_cursor_uses_the_codec() {
+    for codec in CODECS {
+        let doc = document(".é😀 > Button:hover {}\n", codec);
+        let position = position_after(&doc, "Button:");
+        assert_eq!(
+            doc.get_text_before_cursor(position).as_deref(),
+            Some(".é😀 > Button:"),
+            "{:?}",
+            codec
+        );
+    }
+}
+
+#[test]
+fn completion_after_non_ascii_selector() {
+    for codec in CODECS {
+        let doc = document(".é😀 > Button:hover {\n}\n", codec);
+        let position = position_after(&doc, "Button:");
+        let items = get_completions(
+            &doc,
+            position,
+            &Settings::default(),
+            &HashSet::new(),
+            &HashSet::new(),
+        );
+        assert!(
+            items.iter().any(|item| item.label == "focus"),
+            "{:?}: expected pseudo-class completions",
+            codec
+        );
+    }
+}
+
+#[test]
+fn hover_after_non_ascii_selector() {
+    for codec in CODECS {
+        let doc = document(".é😀:hover {\n}\n", codec);
+        let hover = get_hover(&doc, position_after(&doc, "😀:")).expect("hover");
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("expected markup");
+        };
+        assert!(content.value.starts_with("## :hover"), "{:?}", codec);
+    }
+}
+
+#[test]
+fn code_actions_after_non_ascii_selector() {
+    let text = ".é😀 { -unity-background-image-tint-color: red; }\n.ü😀: hover { color: red; }\n";
+    let uri = Url::parse("file:///theme.uss").unwrap();
+    for codec in CODECS {
+        let doc = document(text, codec);
+        let diagnostics = get_diagnostics(&doc, &uri, &Settings::default(), &HashSet::new());
+        let edits: Vec<TextEdit> = get_code_actions(&doc, &uri, &diagnostics)
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => action.edit?.changes?.remove(&uri),
+                CodeActionOrCommand::Command(_) => None,
+            })
+            .flatten()
+            .collect();
+        let mut doc = doc;
+        assert_eq!(
+            apply_edits(&mut doc, edits),
+            ".é😀 { background-image: url(\"\"); -unity-background-image-tint-color: red; }\n.ü😀:hover { color: red; }\n",
+            "{:?}",
+            codec
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! `preloadFiles` are indexed during `initialize`, and missing ones are reported
+
+mod common;
+
+use common::Server;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+/// An empty workspace folder next to a design-token file
+fn workspace(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("uss-preload-{}-{}", name, std::process::id()));
+    let root = dir.join("project");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(dir.join("tokens.uss"), ":root { --accent: red; }\n").unwrap();
+    root
+}
+
+fn is_show_message(message: &Value) -> bool {
+    message["method"] == "window/showMessage"
+}
+
+#[test]
+fn preloaded_variables_are_defined() {
+    let root = workspace("defined");
+    let mut server = Server::start_with(
+        json!({ "preloadFiles": ["../tokens.uss"] }),
+        Some(&root),
+        json!({}),
+        &[],
+    );
+    server.open("file:///project/a.uss", ".a { color: var(--accent); }\n");
+
+    let published = server
+        .wait_for(|message| message["method"] == "textDocument/publishDiagnostics")
+        .expect("diagnostics");
+    assert_eq!(
+        published["params"]["diagnostics"],
+        json!([]),
+        "{}",
+        published
+    );
+    let status = server.request("uss/status", json!(null))["result"].clone();
+    assert_eq!(status["index"]["state"], "ok", "{}", status);
+    let _ = std::fs::remove_dir_all(root.parent().unwrap());
+}
+
+#[test]
+fn missing_preload_files_are_shown_before_the_scan() {
+    let root = workspace("missing");
+    std::fs::remove_dir(&root).unwrap();
+    let tokens = root.parent().unwrap().join("tokens.uss");
+    let mut server = Server::start_with(
+        json!({ "preloadFiles": [tokens, "missing.uss"] }),
+        Some(&root),
+        json!({}),
+        &[],
+    );
+
+    // Shown during `initialize`, so it comes before the summary of the failed scan
+    let warning = server.wait_for(is_show_message).expect("preload warning");
+    assert_eq!(warning["params"]["type"], 2, "{}", warning);
+    assert_eq!(
+        warning["params"]["message"],
+        "USS: Could not preload missing.uss: variables defined there are reported as undefined.",
+    );
+
+    let summary = server.wait_for(is_show_message).expect("startup summary");
+    let summary = summary["params"]["message"].as_str().unwrap();
+    assert!(
+        summary.contains("Cannot read the workspace folder"),
+        "{}",
+        summary
+    );
+    assert!(
+        summary.contains("Could not preload missing.uss"),
+        "{}",
+        summary
+    );
+    let _ = std::fs::remove_dir_all(root.parent().unwrap());
+}
@@ -0,0 +1,154 @@
+//! Failures of server subsystems are reported by `uss/status`
+
+mod common;
+
+use common::Server;
+use lsp_types::Url;
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Client capabilities allowing the file watcher to be registered
+fn watcher_capabilities() -> Value {
+    json!({ "workspace": { "didChangeWatchedFiles": { "dynamicRegistration": true } } })
+}
+
+/// Wait for the startup summary, sent once the workspace scan has finished
+fn startup_summary(server: &mut Server) -> String {
+    let message = server
+        .wait_for(|message| message["method"] == "window/showMessage")
+        .expect("startup status message");
+    message["params"]["message"].as_str().unwrap().to_string()
+}
+
+fn status(server: &mut Server) -> Value {
+    server.request("uss/status", json!(null))["result"].clone()
+}
+
+#[test]
+fn malformed_settings_are_reported() {
+    let mut server = Server::start(json!({ "diagnostics": { "maxConcurrency": "many" } }));
+
+    let status = status(&mut server);
+    assert_eq!(status["settings"]["state"], "degraded", "{}", status);
+    let message = status["settings"]["message"].as_str().unwrap();
+    assert!(message.contains("malformed"), "{}", message);
+    assert!(
+        message.contains("diagnostics.maxConcurrency"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn valid_settings_apply_next_to_malformed_ones() {
+    let mut server = Server::start(json!({
+        "diagnostics": { "maxConcurrency": "many", "rules": { "missing-semicolon": false } }
+    }));
+    server.open("file:///a.uss", ".a {\n    colr: red\n}\n");
+
+    let published = server
+        .wait_for(|message| message["method"] == "textDocument/publishDiagnostics")
+        .expect("diagnostics");
+    let messages: Vec<&str> = published["params"]["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["message"].as_str().unwrap())
+        .collect();
+    assert!(
+        !messages.iter().any(|m| m.contains("Missing semicolon")),
+        "{:?}",
+        messages
+    );
+    assert!(
+        messages.iter().any(|m| m.contains("Unknown USS property")),
+        "{:?}",
+        messages
+    );
+}
+
+#[test]
+fn unreadable_root_is_reported() {
+    let root = Path::new("/nonexistent/uss-workspace");
+    let mut server = Server::start_with(json!({}), Some(root), watcher_capabilities(), &[]);
+
+    let summary = startup_summary(&mut server);
+    assert!(
+        summary.contains("Cannot read the workspace folder"),
+        "{}",
+        summary
+    );
+    let status = status(&mut server);
+    assert_eq!(status["index"]["state"], "unavailable", "{}", status);
+    assert_eq!(status["watcher"]["state"], "ok", "{}", status);
+    assert_eq!(status["uxml"]["state"], "unavailable", "{}", status);
+}
+
+#[test]
+fn uxml_is_not_scanned_without_a_root() {
+    let mut server = Server::start(json!({}));
+
+    let status = status(&mut server);
+    assert_eq!(status["uxml"]["state"], "unavailable", "{}", status);
+    assert!(status["uxml"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("UXML files are not scanned"));
+}
+
+#[test]
+fn uxml_classes_are_offered_and_unreadable_files_reported() {
+    let root = std::env::temp_dir().join(format!("uss-status-uxml-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(
+        root.join("main.uxml"),
+        "<ui:UXML>\n    <ui:VisualElement class=\"toolbar\" />\n</ui:UXML>\n",
+    )
+    .unwrap();
+    std::fs::write(root.join("broken.uxml"), [0xff, 0xfe, 0x00]).unwrap();
+    let mut server = Server::start_with(json!({}), Some(&root), watcher_capabilities(), &[]);
+
+    let summary = startup_summary(&mut server);
+    assert!(
+        summary.contains("1 UXML file(s) in the workspace could not be read"),
+        "{}",
+        summary
+    );
+    let status = status(&mut server);
+    assert_eq!(status["uxml"]["state"], "degraded", "{}", status);
+    assert_eq!(status["index"]["state"], "ok", "{}", status);
+
+    let uri = Url::from_file_path(root.join("theme.uss"))
+        .unwrap()
+        .to_string();
+    server.open(&uri, ".");
+    let response = server.request(
+        "textDocument/completion",
+        json!({ "textDocument": { "uri": uri }, "position": { "line": 0, "character": 1 } }),
+    );
+    let items = response["result"].as_array().unwrap();
+    let toolbar = items
+        .iter()
+        .find(|item| item["label"] == "toolbar")
+        .unwrap_or_else(|| panic!("no UXML class in {}", response));
+    assert_eq!(toolbar["detail"], "Class used in UXML");
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn rejected_watcher_registration_is_reported() {
+    let root = std::env::temp_dir().join(format!("uss-status-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    let mut server = Server::start_with(json!({}), Some(&root), watcher_capabilities(), &[]);
+    server.fail_server_requests("client/registerCapability");
+
+    let summary = startup_summary(&mut server);
+    assert!(summary.contains("File watching unavailable"), "{}", summary);
+    let status = status(&mut server);
+    assert_eq!(status["watcher"]["state"], "unavailable", "{}", status);
+    assert!(status["watcher"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("rejected by the test"));
+    let _ = std::fs::remove_dir(&root);
+}
@@ -0,0 +1,57 @@
+//! `uss.variablesReport` only writes its output inside the workspace
+
+mod common;
+
+use common::Server;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+/// A workspace folder with one style sheet
+fn workspace(name: &str) -> PathBuf {
+    let root = std::env::temp_dir()
+        .join(format!("uss-report-{}-{}", name, std::process::id()))
+        .join("project");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("theme.uss"), ":root { --accent: red; }\n").unwrap();
+    root
+}
+
+fn variables_report(server: &mut Server, output: &str) -> Value {
+    server.request(
+        "workspace/executeCommand",
+        json!({ "command": "uss.variablesReport", "arguments": [{ "output": output }] }),
+    )
+}
+
+#[test]
+fn report_written_inside_the_workspace() {
+    let root = workspace("inside");
+    let mut server = Server::start_with(json!({}), Some(&root), json!({}), &[]);
+
+    let response = variables_report(&mut server, "variables.md");
+    assert!(response.get("error").is_none(), "{}", response);
+    assert!(std::fs::read_to_string(root.join("variables.md"))
+        .unwrap()
+        .starts_with("# USS Variables Report"));
+}
+
+#[test]
+fn report_outside_the_workspace_is_rejected() {
+    let root = workspace("outside");
+    let outside = root.parent().unwrap().join("escaped.md");
+    let mut server = Server::start_with(json!({}), Some(&root), json!({}), &[]);
+
+    for output in [
+        "../escaped.md".to_string(),
+        "reports/../../escaped.md".to_string(),
+        outside.to_string_lossy().into_owned(),
+    ] {
+        let response = variables_report(&mut server, &output);
+        assert_eq!(response["error"]["code"], -32602, "{}", response);
+        assert!(response["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("inside the workspace"));
+    }
+    assert!(!outside.exists());
+}
@@ -0,0 +1,86 @@
+//! Generated documents served through `uss/virtualContent`
+
+mod common;
+
+use common::Server;
+use serde_json::{json, Value};
+
+const URI: &str = "file:///virtual/sheet.uss";
+
+fn execute(server: &mut Server, command: &str, arguments: Value) -> Value {
+    let response = server.request(
+        "workspace/executeCommand",
+        json!({ "command": command, "arguments": arguments }),
+    );
+    response["result"].clone()
+}
+
+fn fetch(server: &mut Server, uri: &Value) -> Value {
+    server.request("uss/virtualContent", json!({ "uri": uri }))["result"].clone()
+}
+
+fn explain(server: &mut Server) -> Value {
+    let result = execute(
+        server,
+        "uss.explainStyles",
+        json!([URI, { "line": 0, "character": 1 }]),
+    );
+    result["uri"].clone()
+}
+
+#[test]
+fn fetches_explanations_and_previews() {
+    let mut server = Server::start(json!({}));
+    server.open(URI, ".panel{color:red;}\n");
+
+    let explanation = explain(&mut server);
+    assert!(explanation
+        .as_str()
+        .unwrap()
+        .starts_with("uss-preview://explain/"));
+    let content = fetch(&mut server, &explanation);
+    assert_eq!(content["languageId"], "markdown");
+    assert_eq!(content["sourceUri"], URI);
+    assert!(content["content"].as_str().unwrap().contains("`.panel`"));
+
+    let preview = execute(&mut server, "uss.previewFormatted", json!([URI]))["uri"].clone();
+    let content = fetch(&mut server, &preview);
+    assert_eq!(content["languageId"], "uss");
+    assert_eq!(content["content"], ".panel {\n    color: red;\n}\n");
+}
+
+#[test]
+fn edits_invalidate_generated_documents() {
+    let mut server = Server::start(json!({}));
+    server.open(URI, ".panel { color: red; }\n");
+    let explanation = explain(&mut server);
+
+    server.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{ "text": ".panel { color: blue; }\n" }]
+        }),
+    );
+
+    assert!(fetch(&mut server, &explanation).is_null());
+    // A new explanation reflects the edit
+    let explanation = explain(&mut server);
+    let content = fetch(&mut server, &explanation);
+    assert!(content["content"].as_str().unwrap().contains("blue"));
+}
+
+#[test]
+fn closing_generated_documents_drops_them() {
+    let mut server = Server::start(json!({}));
+    server.open(URI, ".panel { color: red; }\n");
+    let report = execute(&mut server, "uss.variablesReport", json!([]))["uri"].clone();
+    assert!(!fetch(&mut server, &report).is_null());
+
+    server.notify(
+        "textDocument/didClose",
+        json!({ "textDocument": { "uri": report } }),
+    );
+
+    assert!(fetch(&mut server, &report).is_null());
+}
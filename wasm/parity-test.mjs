@@ -0,0 +1,35 @@
+// Checks that the WebAssembly build reports the same analysis as the native
+// build for every fixture.
+//
+//   wasm-pack build --target nodejs --out-dir wasm/pkg -- --no-default-features --features wasm
+//   cargo build
+//   node wasm/parity-test.mjs
+
+import { execFileSync } from "node:child_process";
+import { readdirSync, readFileSync } from "node:fs";
+import { createRequire } from "node:module";
+import { dirname, join } from "node:path";
+import { fileURLToPath } from "node:url";
+import assert from "node:assert/strict";
+
+const here = dirname(fileURLToPath(import.meta.url));
+const require = createRequire(import.meta.url);
+const wasm = require(join(here, "pkg", "uss_language_server.js"));
+const server = join(here, "..", "target", "debug", "uss-language-server");
+
+const fixtures = join(here, "fixtures");
+let failures = 0;
+for (const name of readdirSync(fixtures).filter((f) => f.endsWith(".uss"))) {
+  const path = join(fixtures, name);
+  const native = JSON.parse(execFileSync(server, ["analyze", path], { encoding: "utf8" }));
+  const browser = JSON.parse(wasm.analyze_json(readFileSync(path, "utf8")));
+  try {
+    assert.deepEqual(browser, native);
+    assert.equal(wasm.format(readFileSync(path, "utf8"), 4), native.formatted);
+    console.log(`ok   ${name}`);
+  } catch (e) {
+    failures++;
+    console.log(`FAIL ${name}\n${e.message}`);
+  }
+}
+process.exit(failures ? 1 : 0);